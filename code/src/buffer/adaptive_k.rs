@@ -0,0 +1,55 @@
+/// Experimental policy that nudges the replacer's `k` toward the observed
+/// reuse-distance distribution.
+///
+/// A frame with reuse distance `d` (ticks between consecutive accesses) picks up
+/// roughly `capacity / d` accesses while a full pool's worth of other accesses
+/// go by. That is the amount of history that is actually useful to rank it, so
+/// after every `window` samples the median distance is turned into a target
+/// `capacity / median` and `k` moves one step toward it, clamped to
+/// `[k_min, k_max]`.
+#[derive(Clone, Debug)]
+pub(crate) struct AdaptiveK {
+    k_min: usize,
+    k_max: usize,
+    window: usize,
+    samples: Vec<u64>,
+}
+
+impl AdaptiveK {
+    pub(crate) fn new(k_min: usize, k_max: usize, window: usize) -> Self {
+        Self {
+            k_min,
+            k_max,
+            window,
+            samples: Vec::with_capacity(window),
+        }
+    }
+
+    /// Record one reuse distance. Returns the new `k` when an adjustment is due
+    /// and it differs from `current_k`.
+    pub(crate) fn observe(
+        &mut self,
+        reuse_distance: u64,
+        current_k: usize,
+        capacity: usize,
+    ) -> Option<usize> {
+        self.samples.push(reuse_distance);
+        if self.samples.len() < self.window {
+            return None;
+        }
+
+        self.samples.sort_unstable();
+        let median = self.samples[self.samples.len() / 2].max(1);
+        self.samples.clear();
+
+        let target = (capacity as u64 / median) as usize;
+        let target = target.clamp(self.k_min, self.k_max);
+        let next = match target.cmp(&current_k) {
+            std::cmp::Ordering::Greater => current_k + 1,
+            std::cmp::Ordering::Less => current_k - 1,
+            std::cmp::Ordering::Equal => current_k,
+        };
+        let next = next.clamp(self.k_min, self.k_max);
+        (next != current_k).then_some(next)
+    }
+}
@@ -1,27 +1,73 @@
 use std::cmp::Ordering;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
 
+#[cfg(feature = "adaptive-k")]
+use super::adaptive_k::AdaptiveK;
 use crate::error::{CustomError, CustomResult};
 
 type FrameId = usize;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct LRUKNode {
     /// K parameter: distance is measured to the K-th most recent access.
     k: usize,
-    /// At most `k` timestamps in ascending recency:
-    /// - `front()` is the K-th most recent (oldest in the kept window)
-    /// - `back()` is the most recent
-    history: VecDeque<u64>,
+    /// At most `k` timestamps in ascending recency (oldest is the K-th most
+    /// recent, newest is the most recent), possibly packed by
+    /// [`compact`](Self::compact).
+    history: Window,
+    /// Timestamp of the access that created this node.
+    first_ts: u64,
+    /// Priority class; lower classes are evicted first.
+    class: u8,
     /// Whether this frame is allowed to be evicted.
     pub is_evictable: bool,
 }
 
+/// Storage for a node's access window.
+#[derive(Clone, Debug)]
+enum Window {
+    /// Plain timestamps, oldest first.
+    Full(VecDeque<u64>),
+    /// Produced by [`LRUKNode::compact`]: the oldest timestamp as two 32-bit
+    /// halves (low first), then every later one as an offset from it. Holds
+    /// at least three timestamps, in one allocation half the size of `Full`'s.
+    Packed(Box<[u32]>),
+}
+
+// Packing must not cost uncompacted nodes anything.
+const _: () = assert!(size_of::<Window>() == size_of::<VecDeque<u64>>());
+
+impl Window {
+    /// Oldest remembered timestamp (if any).
+    fn oldest(&self) -> Option<u64> {
+        match self {
+            Window::Full(h) => h.front().copied(),
+            Window::Packed(p) => Some(u64::from(p[0]) | (u64::from(p[1]) << 32)),
+        }
+    }
+
+    /// Remembered timestamps, oldest first.
+    fn iter(&self) -> Box<dyn Iterator<Item = u64> + '_> {
+        match self {
+            Window::Full(h) => Box::new(h.iter().copied()),
+            Window::Packed(p) => {
+                let oldest = self.oldest().expect("packed window is non-empty");
+                let rest = p[2..].iter().map(move |&d| oldest + u64::from(d));
+                Box::new(std::iter::once(oldest).chain(rest))
+            }
+        }
+    }
+}
+
 impl LRUKNode {
-    fn new(k: usize) -> Self {
+    fn new(k: usize, first_ts: u64) -> Self {
         Self {
             k,
-            history: VecDeque::with_capacity(k),
+            history: Window::Full(VecDeque::with_capacity(k)),
+            first_ts,
+            class: 0,
             is_evictable: false,
         }
     }
@@ -29,39 +75,260 @@ impl LRUKNode {
     /// Record a new access at timestamp `ts`.
     /// Keeps at most `k` entries: drops oldest when exceeding k.
     fn record_access(&mut self, ts: u64) {
-        if self.history.len() == self.k {
-            self.history.pop_front();
+        let k = self.k;
+        let history = self.expand();
+        if history.len() == k {
+            history.pop_front();
         }
-        self.history.push_back(ts);
+        history.push_back(ts);
     }
 
     /// Number of accesses we currently remember (≤ k).
     #[inline]
     fn len(&self) -> usize {
-        self.history.len()
+        match &self.history {
+            Window::Full(h) => h.len(),
+            Window::Packed(p) => p.len() - 1,
+        }
     }
 
     /// Most recent access time (if any).
     #[inline]
     fn last_ts(&self) -> Option<u64> {
-        self.history.back().copied()
+        match &self.history {
+            Window::Full(h) => h.back().copied(),
+            Window::Packed(p) => Some(self.history.oldest()? + u64::from(*p.last()?)),
+        }
+    }
+
+    /// Whether this node has fewer than `k` references (∞ K-distance).
+    #[inline]
+    pub(crate) fn is_infinite(&self) -> bool {
+        self.len() < self.k
     }
 
     /// K-th most recent access time (only defined if len() == k).
     #[inline]
     fn kth_ts(&self) -> Option<u64> {
-        if self.history.len() == self.k {
-            self.history.front().copied()
-        } else {
+        if self.is_infinite() {
             None
+        } else {
+            self.history.oldest()
+        }
+    }
+
+    /// Remembered timestamps, oldest-first.
+    fn history_vec(&self) -> Vec<u64> {
+        self.history.iter().collect()
+    }
+
+    /// Pack a window of three or more timestamps as 32-bit offsets from the
+    /// oldest, roughly halving its heap footprint. Lossless: skipped if an
+    /// offset doesn't fit.
+    fn compact(&mut self) {
+        let Window::Full(h) = &mut self.history else {
+            return;
+        };
+        if h.len() <= 2 {
+            h.shrink_to_fit();
+            return;
+        }
+        let oldest = h[0];
+        let offsets: Result<Vec<u32>, _> = h
+            .iter()
+            .skip(1)
+            .map(|&ts| u32::try_from(ts - oldest))
+            .collect();
+        if let Ok(offsets) = offsets {
+            let mut packed = Vec::with_capacity(offsets.len() + 2);
+            packed.extend([oldest as u32, (oldest >> 32) as u32]);
+            packed.extend(offsets);
+            self.history = Window::Packed(packed.into_boxed_slice());
+        }
+    }
+
+    /// Heap bytes held by the window, to measure what compaction saves.
+    #[cfg(test)]
+    pub(crate) fn history_heap_bytes(&self) -> usize {
+        match &self.history {
+            Window::Full(h) => h.capacity() * size_of::<u64>(),
+            Window::Packed(p) => p.len() * size_of::<u32>(),
+        }
+    }
+
+    /// Change this node's `k`, keeping the most recent `k` timestamps.
+    fn set_k(&mut self, k: usize) {
+        let old_k = self.k;
+        let history = self.expand();
+        while history.len() > k {
+            history.pop_front();
         }
+        if k > old_k {
+            history.reserve_exact(k - history.len());
+        } else {
+            history.shrink_to(k);
+        }
+        self.k = k;
+    }
+
+    /// Replace the window with `k` copies of `ts` (K-distance 0 at time `ts`).
+    fn fill(&mut self, ts: u64) {
+        self.history = Window::Full(std::iter::repeat_n(ts, self.k).collect());
+    }
+
+    /// Unpack a [`compact`](Self::compact)ed window (a no-op otherwise) and
+    /// return the plain timestamps.
+    fn expand(&mut self) -> &mut VecDeque<u64> {
+        if let Window::Packed(_) = self.history {
+            let mut h = VecDeque::with_capacity(self.k);
+            h.extend(self.history.iter());
+            self.history = Window::Full(h);
+        }
+        match &mut self.history {
+            Window::Full(h) => h,
+            Window::Packed(_) => unreachable!("just expanded"),
+        }
+    }
+}
+
+/// How `evict` chooses among frames with equal K-distance and last access.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Smallest `FrameId` first (default).
+    #[default]
+    LowestId,
+    /// Smallest `FrameId` strictly greater than the last victim first,
+    /// wrapping around, so genuinely tied frames take turns being evicted.
+    RoundRobin,
+    /// Pseudo-random order from a seeded xorshift generator that advances on
+    /// every eviction. Reproducible for a given seed and sequence of calls.
+    Jitter(u64),
+}
+
+/// One xorshift64 step. `state` must be non-zero.
+fn xorshift64(mut state: u64) -> u64 {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    state
+}
+
+/// Seed the jitter generator; xorshift needs a non-zero state.
+fn jitter_seed(seed: u64) -> u64 {
+    if seed == 0 {
+        0x9E37_79B9_7F4A_7C15
+    } else {
+        seed
+    }
+}
+
+/// splitmix64 finalizer, used to derive a per-frame rank from the jitter state.
+fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// State change reported to [`LRUKReplacer::subscribe`] receivers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplacerEvent {
+    /// An access was recorded (the node may have just been created).
+    Accessed(FrameId),
+    /// A frame's evictable flag actually changed to the given value.
+    EvictabilityChanged(FrameId, bool),
+    /// A frame was chosen and removed by an eviction.
+    Evicted(FrameId),
+    /// A frame was removed explicitly via `remove`.
+    Removed(FrameId),
+}
+
+/// Eviction back-pressure derived from how many tracked frames are evictable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pressure {
+    /// Nothing is tracked, or at least `low_watermark` frames can be evicted.
+    Ok,
+    /// Some, but fewer than `low_watermark`, frames can be evicted.
+    Warning,
+    /// Frames are tracked but none of them can be evicted.
+    Critical,
+}
+
+/// Eviction candidate represented as a comparable key.
+/// The victim is the first key according to [`victim_cmp`].
+#[derive(Copy, Clone, Debug)]
+struct Key {
+    /// Priority class (lower classes should be evicted first).
+    class: u8,
+    /// K-distance: (now - kth_ts) for nodes with ≥ K references; ∞ otherwise.
+    k_dist: u128,
+    /// Remembered access count when ranking ∞ frames by count; 0 otherwise
+    /// (fewer accesses should win eviction).
+    count_rank: usize,
+    /// Most recent access (we invert comparison: older last_ts should win eviction).
+    last_ts: u64,
+    /// Final tiebreaker for determinism (smaller rank should be evicted earlier).
+    /// Equals `(0, frame_id)` unless [`TieBreak::RoundRobin`] rotates it or
+    /// [`TieBreak::Jitter`] shuffles it.
+    tie_rank: (u64, FrameId),
+    /// Frame this key describes.
+    frame_id: FrameId,
+}
+
+/// Total eviction order; `Less` means `a` is the better victim:
+/// 1) lower class first
+/// 2) if equal, larger k_dist first (∞ beats finite)
+/// 3) if equal, smaller count_rank first (fewer references beats more)
+/// 4) if equal, smaller last_ts first (older beats newer)
+/// 5) if equal, smaller tie_rank first
+fn victim_cmp(a: Key, b: Key) -> Ordering {
+    a.class
+        .cmp(&b.class)
+        .then(b.k_dist.cmp(&a.k_dist))
+        .then(a.count_rank.cmp(&b.count_rank))
+        .then(a.last_ts.cmp(&b.last_ts))
+        .then(a.tie_rank.cmp(&b.tie_rank))
+}
+
+/// Max-heap adapter: the best victim is the greatest element.
+#[derive(Copy, Clone, Debug)]
+struct HeapKey(Key);
+
+impl PartialEq for HeapKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapKey {}
+
+impl PartialOrd for HeapKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        victim_cmp(other.0, self.0)
     }
 }
 
-#[derive(Debug)]
+/// Whether `a` should be evicted before `b`.
+fn better(a: Key, b: Key) -> bool {
+    victim_cmp(a, b) == Ordering::Less
+}
+
+/// LRU-K replacement policy over buffer-pool frames.
+///
+/// Thread safety: the replacer is `Send` (it can be moved to another thread,
+/// and so can its promotion observer) but not `Sync`: every mutating method
+/// takes `&mut self`. Share it between threads behind a `Mutex`.
 pub struct LRUKReplacer {
     /// Count of evictable frames currently tracked.
     current_size: usize,
+    /// Count of tracked frames that are not evictable.
+    /// `current_size + non_evictable_count == node_store.len()`.
+    non_evictable_count: usize,
     /// Maximum number of frames the replacer can track.
     capacity: usize,
     /// K parameter.
@@ -70,23 +337,272 @@ pub struct LRUKReplacer {
     pub node_store: HashMap<FrameId, LRUKNode>,
     /// Monotonic logical time for ordering accesses.
     current_timestamp: u64,
+    /// Ticks a frame must stay tracked before `evict` may choose it.
+    min_residency: u64,
+    /// Evictable count below which [`pressure`](LRUKReplacer::pressure) warns.
+    low_watermark: usize,
+    /// Whether ∞ frames with more references rank as less evictable.
+    rank_infinite_by_count: bool,
+    /// Tiebreak policy for otherwise equal eviction candidates.
+    tie_break: TieBreak,
+    /// Most recently evicted frame; the round-robin cursor.
+    last_victim: Option<FrameId>,
+    /// xorshift state for [`TieBreak::Jitter`].
+    jitter_state: u64,
+    /// New frames dropped by the last `extend` because capacity was full.
+    last_extend_skipped: usize,
+    /// While frozen, the frames [`freeze`](LRUKReplacer::freeze) made
+    /// non-evictable and that [`thaw`](LRUKReplacer::thaw) will restore.
+    frozen: Option<HashSet<FrameId>>,
+    /// Called when a frame reaches its k-th remembered access (infinite -> finite).
+    promotion_observer: Option<Box<dyn FnMut(FrameId) + Send>>,
+    /// Channels receiving [`ReplacerEvent`]s; dropped once their receiver is gone.
+    subscribers: Vec<Sender<ReplacerEvent>>,
+    /// Reuse-distance sampler driving `k`, when enabled.
+    #[cfg(feature = "adaptive-k")]
+    adaptive_k: Option<AdaptiveK>,
+}
+
+/// Clones are fully independent. The promotion observer and event subscribers
+/// are not carried over; install or subscribe on the clone if needed.
+impl Clone for LRUKReplacer {
+    fn clone(&self) -> Self {
+        Self {
+            current_size: self.current_size,
+            non_evictable_count: self.non_evictable_count,
+            capacity: self.capacity,
+            k: self.k,
+            node_store: self.node_store.clone(),
+            current_timestamp: self.current_timestamp,
+            min_residency: self.min_residency,
+            low_watermark: self.low_watermark,
+            rank_infinite_by_count: self.rank_infinite_by_count,
+            tie_break: self.tie_break,
+            last_victim: self.last_victim,
+            jitter_state: self.jitter_state,
+            last_extend_skipped: self.last_extend_skipped,
+            frozen: self.frozen.clone(),
+            promotion_observer: None,
+            subscribers: Vec::new(),
+            #[cfg(feature = "adaptive-k")]
+            adaptive_k: self.adaptive_k.clone(),
+        }
+    }
+}
+
+/// Record an access for each frame id, as by [`LRUKReplacer::record_access`].
+///
+/// `extend` can't return an error, so new frames that would exceed capacity
+/// are skipped (existing frames are always touched); see
+/// [`LRUKReplacer::last_extend_skipped`].
+impl Extend<FrameId> for LRUKReplacer {
+    fn extend<I: IntoIterator<Item = FrameId>>(&mut self, iter: I) {
+        self.last_extend_skipped = 0;
+        for frame_id in iter {
+            if self.record_access(frame_id).is_err() {
+                self.last_extend_skipped += 1;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for LRUKReplacer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("LRUKReplacer");
+        d.field("current_size", &self.current_size)
+            .field("non_evictable_count", &self.non_evictable_count)
+            .field("capacity", &self.capacity)
+            .field("k", &self.k)
+            .field("node_store", &self.node_store)
+            .field("current_timestamp", &self.current_timestamp)
+            .field("min_residency", &self.min_residency)
+            .field("low_watermark", &self.low_watermark)
+            .field("rank_infinite_by_count", &self.rank_infinite_by_count)
+            .field("tie_break", &self.tie_break)
+            .field("last_victim", &self.last_victim)
+            .field("jitter_state", &self.jitter_state)
+            .field("last_extend_skipped", &self.last_extend_skipped)
+            .field("frozen", &self.frozen)
+            .field("promotion_observer", &self.promotion_observer.is_some())
+            .field("subscribers", &self.subscribers.len());
+        #[cfg(feature = "adaptive-k")]
+        d.field("adaptive_k", &self.adaptive_k);
+        d.finish()
+    }
 }
 
 impl LRUKReplacer {
     /// Create a new LRU-K replacer with `capacity` frames and parameter `k`.
     ///
+    /// Prefer [`try_new`](Self::try_new) when `capacity` or `k` come from
+    /// configuration or other untrusted input.
+    ///
     /// # Panics
     /// Panics if `k == 0` or `capacity == 0`.
     pub fn new(capacity: usize, k: usize) -> Self {
-        assert!(k >= 1, "k must be >= 1");
-        assert!(capacity >= 1, "capacity must be >= 1");
-        Self {
+        Self::try_new(capacity, k).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fallible [`new`](Self::new): returns [`CustomError::Internal`] instead of
+    /// panicking if `k == 0` or `capacity == 0`.
+    pub fn try_new(capacity: usize, k: usize) -> CustomResult<Self> {
+        if k == 0 {
+            return Err(CustomError::Internal("k must be >= 1".into()));
+        }
+        if capacity == 0 {
+            return Err(CustomError::Internal("capacity must be >= 1".into()));
+        }
+        Ok(Self {
             current_size: 0,
+            non_evictable_count: 0,
             capacity,
             k,
             node_store: HashMap::with_capacity(capacity),
             current_timestamp: 0,
+            min_residency: 0,
+            low_watermark: capacity / 4,
+            rank_infinite_by_count: false,
+            tie_break: TieBreak::LowestId,
+            last_victim: None,
+            jitter_state: 0,
+            last_extend_skipped: 0,
+            frozen: None,
+            promotion_observer: None,
+            subscribers: Vec::new(),
+            #[cfg(feature = "adaptive-k")]
+            adaptive_k: None,
+        })
+    }
+
+    /// Subscribe to a stream of [`ReplacerEvent`]s for every subsequent
+    /// mutation. Any number of subscribers may exist; dropping the receiver
+    /// unsubscribes.
+    pub fn subscribe(&mut self) -> Receiver<ReplacerEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Current `k` parameter.
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    /// Let `k` adapt to observed reuse distances within `[k_min, k_max]`,
+    /// re-evaluated after every `window` re-accesses. Experimental.
+    ///
+    /// The current `k` is first clamped into range. Returns an error if
+    /// `k_min == 0`, `k_min > k_max` or `window == 0`.
+    #[cfg(feature = "adaptive-k")]
+    pub fn enable_adaptive_k(
+        &mut self,
+        k_min: usize,
+        k_max: usize,
+        window: usize,
+    ) -> CustomResult<()> {
+        if k_min == 0 || k_min > k_max || window == 0 {
+            return Err(CustomError::Internal(format!(
+                "invalid adaptive k range [{k_min}, {k_max}] with window {window}"
+            )));
+        }
+        self.apply_k(self.k.clamp(k_min, k_max));
+        self.adaptive_k = Some(AdaptiveK::new(k_min, k_max, window));
+        Ok(())
+    }
+
+    /// Reconfigure `k` at runtime.
+    ///
+    /// Shrinking keeps each node's most recent `k` timestamps, so K-distances
+    /// are immediately recomputed from the shorter window. Growing keeps
+    /// histories as-is; nodes become finite again once they accumulate `k`
+    /// references. Returns an error if `k == 0`.
+    pub fn set_k(&mut self, k: usize) -> CustomResult<()> {
+        if k == 0 {
+            return Err(CustomError::Internal("k must be >= 1".into()));
+        }
+        self.apply_k(k);
+        Ok(())
+    }
+
+    /// Switch every node to a new `k`, truncating histories when shrinking.
+    fn apply_k(&mut self, k: usize) {
+        if k == self.k {
+            return;
+        }
+        for node in self.node_store.values_mut() {
+            node.set_k(k);
+        }
+        self.k = k;
+    }
+
+    /// Require frames to be tracked for at least `ticks` logical ticks (since
+    /// their first access) before [`evict`](Self::evict) may choose them.
+    ///
+    /// Prevents thrashing on just-admitted frames. Such frames still count
+    /// towards [`size`](Self::size); they are only passed over as victims.
+    pub fn set_min_residency(&mut self, ticks: u64) {
+        self.min_residency = ticks;
+    }
+
+    /// Whether `node` may be chosen as a victim right now.
+    fn is_candidate(&self, node: &LRUKNode) -> bool {
+        self.frozen.is_none()
+            && node.is_evictable
+            && self.current_timestamp.saturating_sub(node.first_ts) >= self.min_residency
+    }
+
+    /// Set the evictable count below which [`pressure`](Self::pressure) reports
+    /// [`Pressure::Warning`]. Defaults to a quarter of the capacity.
+    pub fn set_low_watermark(&mut self, low_watermark: usize) {
+        self.low_watermark = low_watermark;
+    }
+
+    /// Current eviction back-pressure, so a background task can proactively
+    /// unpin or flush before callers run out of victims. Always
+    /// [`Pressure::Critical`] while [frozen](Self::freeze) with frames tracked.
+    pub fn pressure(&self) -> Pressure {
+        if self.node_store.is_empty() {
+            Pressure::Ok
+        } else if self.current_size == 0 || self.frozen.is_some() {
+            Pressure::Critical
+        } else if self.current_size < self.low_watermark {
+            Pressure::Warning
+        } else {
+            Pressure::Ok
+        }
+    }
+
+    /// Among frames with fewer than `k` references (all ∞ K-distance), evict
+    /// the ones with fewer remembered accesses first, before falling back to
+    /// the older last access. Off by default (plain LRU-K).
+    ///
+    /// Helps large-`k` setups where a frame referenced often, but fewer than
+    /// `k` times, would otherwise look as cold as a one-off access.
+    pub fn set_rank_infinite_by_count(&mut self, enabled: bool) {
+        self.rank_infinite_by_count = enabled;
+    }
+
+    /// Select how ties between otherwise equal eviction candidates are broken.
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        if let TieBreak::Jitter(seed) = tie_break {
+            self.jitter_state = jitter_seed(seed);
         }
+        self.tie_break = tie_break;
+    }
+
+    /// Builder form of `set_tie_break(TieBreak::Jitter(seed))`, for load tests
+    /// that want reproducible variance among tied victims.
+    pub fn with_jitter(mut self, seed: u64) -> Self {
+        self.set_tie_break(TieBreak::Jitter(seed));
+        self
+    }
+
+    /// Install a hook called with the frame id whenever a frame moves from
+    /// infinite to finite K-distance, i.e. on the access that brings its
+    /// history to exactly `k` entries. Replaces any previous observer.
+    pub fn set_promotion_observer(&mut self, observer: Box<dyn FnMut(FrameId) + Send>) {
+        self.promotion_observer = Some(observer);
     }
 
     /// Record an access to `frame_id`.
@@ -95,56 +611,356 @@ impl LRUKReplacer {
     /// - Increments the global logical timestamp per access.
     /// - Returns an error if the number of **tracked frames** would exceed capacity.
     pub fn record_access(&mut self, frame_id: FrameId) -> CustomResult<()> {
-        // Bump logical time (monotonic). This avoids subtle underflow later.
-        // If you prefer overflow-wrapping semantics, replace with `self.current_timestamp = self.current_timestamp.wrapping_add(1);`
-        if let Some(next) = self.current_timestamp.checked_add(1) {
-            self.current_timestamp = next;
-        } else {
-            // Extremely unlikely in practice. Reset to 0 and continue deterministically.
-            self.current_timestamp = 0;
+        // Fail before touching the clock so an error leaves no trace.
+        self.ensure_room(frame_id)?;
+        let ts = self.advance_clock();
+        self.record_at(frame_id, ts)
+    }
+
+    /// Advance logical time by one without recording an access, and return
+    /// the new timestamp. Overflow is handled exactly as in
+    /// [`record_access`](Self::record_access).
+    ///
+    /// Useful for time-based policies such as `min_residency`, which age
+    /// frames by the clock rather than by accesses.
+    pub fn tick(&mut self) -> u64 {
+        self.advance_clock()
+    }
+
+    /// Current logical timestamp (that of the most recent access or tick).
+    #[inline]
+    pub fn now(&self) -> u64 {
+        self.current_timestamp
+    }
+
+    /// Bump logical time (monotonic) and return the new value.
+    ///
+    /// If the clock would overflow, every remembered timestamp is first
+    /// rebased (see [`rebase_timestamps`](Self::rebase_timestamps)), so the
+    /// eviction order is unaffected.
+    fn advance_clock(&mut self) -> u64 {
+        if self.current_timestamp == u64::MAX {
+            self.rebase_timestamps();
+        }
+        self.current_timestamp += 1;
+        self.current_timestamp
+    }
+
+    /// Renumber every remembered timestamp densely from 1, preserving their
+    /// relative order, and move the clock to the largest one.
+    ///
+    /// K-distance and last-access comparisons only depend on that order (the
+    /// clock stays ahead of all of them), so victims are chosen exactly as
+    /// before. Absolute distances shrink, which can only make
+    /// `min_residency` expire sooner.
+    fn rebase_timestamps(&mut self) {
+        let mut all: Vec<u64> = self
+            .node_store
+            .values()
+            .flat_map(|node| node.history.iter().chain([node.first_ts]))
+            .collect();
+        all.sort_unstable();
+        all.dedup();
+
+        let remap = |ts: u64| all.binary_search(&ts).expect("collected above") as u64 + 1;
+        for node in self.node_store.values_mut() {
+            // Packed offsets would not survive renumbering.
+            for ts in node.expand().iter_mut() {
+                *ts = remap(*ts);
+            }
+            node.first_ts = remap(node.first_ts);
+        }
+        self.current_timestamp = all.len() as u64;
+    }
+
+    /// Jump the logical clock, e.g. to exercise overflow handling.
+    #[cfg(test)]
+    pub(crate) fn set_timestamp_for_test(&mut self, ts: u64) {
+        self.current_timestamp = ts;
+    }
+
+    /// Record an access to `frame_id` at an explicit logical timestamp `ts`.
+    ///
+    /// Used to replay captured traces with their original timings. The clock
+    /// jumps to `ts`; returns an error if `ts` is older than the current clock.
+    pub fn record_access_at(&mut self, frame_id: FrameId, ts: u64) -> CustomResult<()> {
+        if ts < self.current_timestamp {
+            return Err(CustomError::Internal(format!(
+                "timestamp {ts} is older than current clock {}",
+                self.current_timestamp
+            )));
+        }
+        self.ensure_room(frame_id)?;
+        self.current_timestamp = ts;
+        self.record_at(frame_id, ts)
+    }
+
+    /// Replay a trace of `(frame_id, ts)` accesses in order with
+    /// [`record_access_at`](Self::record_access_at) semantics.
+    ///
+    /// The whole trace is validated up front: timestamps must be non-decreasing
+    /// and not older than the current clock, and the new frames it introduces
+    /// must fit in the remaining capacity. Otherwise nothing is applied and
+    /// the error names the offending index.
+    pub fn record_access_trace(&mut self, trace: &[(FrameId, u64)]) -> CustomResult<()> {
+        let mut prev = self.current_timestamp;
+        let mut new_frames = HashSet::new();
+        for (i, &(frame_id, ts)) in trace.iter().enumerate() {
+            if ts < prev {
+                return Err(CustomError::Internal(format!(
+                    "trace timestamp goes backwards at index {i}: {ts} < {prev}"
+                )));
+            }
+            prev = ts;
+            if !self.node_store.contains_key(&frame_id)
+                && new_frames.insert(frame_id)
+                && new_frames.len() > self.capacity_remaining()
+            {
+                return Err(CustomError::Internal(format!(
+                    "trace exceeds replacer capacity at index {i}: frame {frame_id}"
+                )));
+            }
+        }
+        for &(frame_id, ts) in trace {
+            self.record_access_at(frame_id, ts)?;
+        }
+        Ok(())
+    }
+
+    /// Warm the replacer from aggregate per-frame access counts.
+    ///
+    /// Each `(frame_id, count)` is recorded as up to `count` consecutive
+    /// [`record_access`](Self::record_access) calls, capped at `k` since only
+    /// the last `k` accesses are remembered. A count of 0 is skipped. Stops at
+    /// the first error (e.g. exceeding capacity).
+    pub fn record_access_counts(&mut self, counts: &[(FrameId, u32)]) -> CustomResult<()> {
+        for &(frame_id, count) in counts {
+            let reps = (count as usize).min(self.k);
+            for _ in 0..reps {
+                self.record_access(frame_id)?;
+            }
         }
+        Ok(())
+    }
 
+    /// Append `ts` to `frame_id`'s history, creating the node if needed.
+    fn record_at(&mut self, frame_id: FrameId, ts: u64) -> CustomResult<()> {
         if let Some(node) = self.node_store.get_mut(&frame_id) {
-            node.record_access(self.current_timestamp);
+            let was = node.len();
+            #[cfg(feature = "adaptive-k")]
+            let reuse_distance = ts.saturating_sub(node.last_ts().unwrap_or(ts));
+            node.record_access(ts);
+            if was < self.k && node.len() == self.k {
+                self.notify_promoted(frame_id);
+            }
+            #[cfg(feature = "adaptive-k")]
+            if let Some(adaptive) = self.adaptive_k.as_mut()
+                && let Some(k) = adaptive.observe(reuse_distance, self.k, self.capacity)
+            {
+                self.apply_k(k);
+            }
+            self.emit(ReplacerEvent::Accessed(frame_id));
             return Ok(());
         }
 
-        // New frame: ensure we don't exceed tracking capacity.
-        if self.node_store.len() >= self.capacity {
+        self.ensure_room(frame_id)?;
+        let mut node = LRUKNode::new(self.k, ts);
+        node.record_access(ts);
+        self.node_store.insert(frame_id, node);
+        self.non_evictable_count += 1;
+        if self.k == 1 {
+            self.notify_promoted(frame_id);
+        }
+        self.emit(ReplacerEvent::Accessed(frame_id));
+        Ok(())
+    }
+
+    /// New frame: ensure we don't exceed tracking capacity.
+    fn ensure_room(&self, frame_id: FrameId) -> CustomResult<()> {
+        if self.node_store.len() >= self.capacity && !self.node_store.contains_key(&frame_id) {
             return Err(CustomError::Internal(
                 "replacer bookkeeping exceeds capacity".to_string(),
             ));
         }
-
-        let mut node = LRUKNode::new(self.k);
-        node.record_access(self.current_timestamp);
-        self.node_store.insert(frame_id, node);
         Ok(())
     }
 
-    /// Set whether a frame is evictable.
+    /// Send `event` to every live subscriber; a no-op when there are none.
+    fn emit(&mut self, event: ReplacerEvent) {
+        if self.subscribers.is_empty() {
+            return;
+        }
+        self.subscribers.retain(|tx| tx.send(event).is_ok());
+    }
+
+    fn notify_promoted(&mut self, frame_id: FrameId) {
+        if let Some(observer) = self.promotion_observer.as_mut() {
+            observer(frame_id);
+        }
+    }
+
+    /// Set whether a frame is evictable and return its previous state.
     ///
     /// Adjusts `current_size` accordingly. Returns an error if the frame does not exist.
-    pub fn set_evictable(&mut self, frame_id: FrameId, set_evictable: bool) -> CustomResult<()> {
+    ///
+    /// While [frozen](Self::freeze), this also takes the frame out of the set
+    /// [`thaw`](Self::thaw) restores: the caller's latest choice wins.
+    pub fn set_evictable(&mut self, frame_id: FrameId, set_evictable: bool) -> CustomResult<bool> {
+        if let Some(frozen) = self.frozen.as_mut() {
+            frozen.remove(&frame_id);
+        }
+        self.flip_evictable(frame_id, set_evictable)
+    }
+
+    /// [`set_evictable`](Self::set_evictable) without touching the frozen set.
+    fn flip_evictable(&mut self, frame_id: FrameId, set_evictable: bool) -> CustomResult<bool> {
         match self.node_store.get_mut(&frame_id) {
             None => Err(CustomError::Internal("frame not found".into())),
             Some(node) => {
                 let was = node.is_evictable;
                 node.is_evictable = set_evictable;
                 match (was, set_evictable) {
-                    (false, true) => self.current_size += 1,
-                    (true, false) => self.current_size -= 1,
+                    (false, true) => {
+                        self.current_size += 1;
+                        self.non_evictable_count -= 1;
+                    }
+                    (true, false) => {
+                        self.current_size -= 1;
+                        self.non_evictable_count += 1;
+                    }
                     _ => {}
                 }
+                if was != set_evictable {
+                    self.emit(ReplacerEvent::EvictabilityChanged(frame_id, set_evictable));
+                }
                 debug_assert_eq!(
                     self.current_size,
                     self.node_store.values().filter(|n| n.is_evictable).count()
                 );
+                Ok(was)
+            }
+        }
+    }
+
+    /// The buffer pool's fetch path in one call: record an access to
+    /// `frame_id` (creating the node if needed) and mark it non-evictable.
+    ///
+    /// On error (capacity exceeded) no frame state is changed and the logical
+    /// clock does not advance.
+    pub fn access_and_pin(&mut self, frame_id: FrameId) -> CustomResult<()> {
+        self.record_access(frame_id)?;
+        self.set_evictable(frame_id, false)?;
+        Ok(())
+    }
+
+    /// Counterpart of [`access_and_pin`](Self::access_and_pin): release the
+    /// pin, leaving the frame `evictable` (or not, if the caller still holds
+    /// other pins). Returns an error if the frame does not exist.
+    pub fn unpin(&mut self, frame_id: FrameId, evictable: bool) -> CustomResult<()> {
+        self.set_evictable(frame_id, evictable)?;
+        Ok(())
+    }
+
+    /// Set evictability for a batch of frames.
+    ///
+    /// Unlike [`set_evictable`](Self::set_evictable) this does not stop at the
+    /// first unknown frame: every tracked frame is updated and the ids that
+    /// were not found are returned.
+    pub fn set_evictable_many(
+        &mut self,
+        frames: &[FrameId],
+        evictable: bool,
+    ) -> CustomResult<Vec<FrameId>> {
+        let mut missing = Vec::new();
+        for &frame_id in frames {
+            if self.node_store.contains_key(&frame_id) {
+                self.set_evictable(frame_id, evictable)?;
+            } else {
+                missing.push(frame_id);
+            }
+        }
+        Ok(missing)
+    }
+
+    /// Force `frame_id` into the hottest finite state: its history becomes `k`
+    /// copies of the current timestamp, i.e. a K-distance of 0.
+    ///
+    /// The frame keeps its evictable flag; it is simply ranked behind every
+    /// other frame until newer accesses overtake it. Does not advance the
+    /// clock. Returns an error if the frame does not exist.
+    ///
+    /// Reported like an access: subscribers get [`ReplacerEvent::Accessed`],
+    /// and the promotion observer fires if the frame had ∞ K-distance.
+    pub fn promote(&mut self, frame_id: FrameId) -> CustomResult<()> {
+        let now = self.current_timestamp;
+        match self.node_store.get_mut(&frame_id) {
+            None => Err(CustomError::Internal("frame not found".into())),
+            Some(node) => {
+                let was_infinite = node.is_infinite();
+                node.fill(now);
+                if was_infinite {
+                    self.notify_promoted(frame_id);
+                }
+                self.emit(ReplacerEvent::Accessed(frame_id));
+                Ok(())
+            }
+        }
+    }
+
+    /// Assign `frame_id` to a priority class (default 0).
+    ///
+    /// [`evict`](Self::evict) only considers a class once every lower class has
+    /// no eligible frames left; LRU-K ordering applies within a class. Returns
+    /// an error if the frame does not exist.
+    pub fn set_class(&mut self, frame_id: FrameId, class: u8) -> CustomResult<()> {
+        match self.node_store.get_mut(&frame_id) {
+            None => Err(CustomError::Internal("frame not found".into())),
+            Some(node) => {
+                node.class = class;
                 Ok(())
             }
         }
     }
 
+    /// Make every currently evictable frame non-evictable and remember which
+    /// ones they were, e.g. for a consistent backup. Until [`thaw`](Self::thaw),
+    /// [`evict`](Self::evict) returns `None` even for frames made evictable
+    /// in the meantime. Freezing again adds to the remembered set.
+    ///
+    /// [`size`](Self::size) drops accordingly (it still counts frames unpinned
+    /// during the freeze) and [`pressure`](Self::pressure) reports
+    /// [`Pressure::Critical`].
+    pub fn freeze(&mut self) {
+        let mut newly: Vec<FrameId> = self
+            .node_store
+            .iter()
+            .filter(|(_, node)| node.is_evictable)
+            .map(|(&frame_id, _)| frame_id)
+            .collect();
+        newly.sort_unstable();
+        for &frame_id in &newly {
+            let _ = self.flip_evictable(frame_id, false);
+        }
+        self.frozen.get_or_insert_default().extend(newly);
+    }
+
+    /// Undo [`freeze`](Self::freeze): make the remembered frames evictable again
+    /// and allow eviction. No-op if not frozen.
+    ///
+    /// Frames whose evictability was set explicitly during the freeze (e.g.
+    /// pinned with [`access_and_pin`](Self::access_and_pin)), or that were
+    /// removed, are left as they are.
+    pub fn thaw(&mut self) {
+        let Some(frozen) = self.frozen.take() else {
+            return;
+        };
+        let mut restore: Vec<FrameId> = frozen.into_iter().collect();
+        restore.sort_unstable();
+        for frame_id in restore {
+            let _ = self.flip_evictable(frame_id, true);
+        }
+    }
+
     /// Remove a frame from the replacer.
     ///
     /// - Returns an error if the frame exists but is **not evictable**.
@@ -156,94 +972,357 @@ impl LRUKReplacer {
                 Err(CustomError::Internal("frame is not evictable".into()))
             }
             Some(_) => {
-                let node = self.node_store.remove(&frame_id).expect("present");
-                if node.is_evictable {
-                    self.current_size -= 1;
-                }
-                debug_assert_eq!(
-                    self.current_size,
-                    self.node_store.values().filter(|n| n.is_evictable).count()
-                );
+                self.detach(frame_id);
+                self.emit(ReplacerEvent::Removed(frame_id));
                 Ok(())
             }
         }
     }
 
+    /// Keep only the frames for which `f` returns `true`.
+    ///
+    /// Unlike [`remove`](Self::remove) this also drops **non-evictable**
+    /// frames: it is an invalidation, not a reclaim. Counters stay in sync and
+    /// a [`ReplacerEvent::Removed`] is emitted per dropped frame.
+    pub fn retain<F: FnMut(FrameId, &LRUKNode) -> bool>(&mut self, mut f: F) {
+        let mut dropped: Vec<FrameId> = self
+            .node_store
+            .iter()
+            .filter(|&(&frame_id, node)| !f(frame_id, node))
+            .map(|(&frame_id, _)| frame_id)
+            .collect();
+        dropped.sort_unstable();
+        for frame_id in dropped {
+            self.detach(frame_id);
+            self.emit(ReplacerEvent::Removed(frame_id));
+        }
+    }
+
+    /// Drop a tracked node from the store, keeping `current_size` in sync.
+    fn detach(&mut self, frame_id: FrameId) {
+        let node = self.node_store.remove(&frame_id).expect("present");
+        if let Some(frozen) = self.frozen.as_mut() {
+            frozen.remove(&frame_id);
+        }
+        if node.is_evictable {
+            self.current_size -= 1;
+        } else {
+            self.non_evictable_count -= 1;
+        }
+        debug_assert_eq!(
+            self.current_size,
+            self.node_store.values().filter(|n| n.is_evictable).count()
+        );
+        debug_assert_eq!(
+            self.current_size + self.non_evictable_count,
+            self.node_store.len()
+        );
+    }
+
     /// Choose a victim frame to evict, if any, and remove it from the replacer.
     ///
     /// Eviction policy (LRU-K):
+    /// - Only the lowest [priority class](Self::set_class) with candidates is considered.
     /// - Prefer frames with **fewer than K references** (treated as ∞ K-distance).
     /// - Among equals, prefer the one with **older most-recent access**.
     /// - Final deterministic tiebreak by `FrameId` (smaller first).
     ///
+    /// With `k == 1` a frame's only remembered access is both its k-th and its
+    /// most recent one, so no tracked frame is ever ∞ and this is exact LRU.
+    ///
     /// Returns `Some(frame_id)` on success and `None` if no evictable frame exists.
     pub fn evict(&mut self) -> Option<FrameId> {
-        // Candidate we will evict (if any), represented as a comparable key.
-        // We pick the MAX key according to our ordering.
-        #[derive(Copy, Clone, Debug)]
-        struct Key {
-            /// K-distance: (now - kth_ts) for nodes with ≥ K references; ∞ otherwise.
-            k_dist: u128,
-            /// Most recent access (we invert comparison: older last_ts should win eviction).
-            last_ts: u64,
-            /// Final tiebreaker for determinism (smaller id should be evicted earlier).
-            frame_id: FrameId,
-        }
-
-        // Manual comparator implementing:
-        // 1) larger k_dist first (∞ beats finite)
-        // 2) if equal, smaller last_ts first (older beats newer)
-        // 3) if equal, smaller frame_id first
-        fn better(a: Key, b: Key) -> bool {
-            match a.k_dist.cmp(&b.k_dist) {
-                Ordering::Greater => true,
-                Ordering::Less => false,
-                Ordering::Equal => match a.last_ts.cmp(&b.last_ts) {
-                    Ordering::Less => true, // older wins
-                    Ordering::Greater => false,
-                    Ordering::Equal => a.frame_id < b.frame_id,
-                },
-            }
-        }
-
-        let mut best: Option<(Key, FrameId)> = None;
+        let victim = self.peek_victim()?;
+        self.detach(victim);
+        self.emit(ReplacerEvent::Evicted(victim));
+        self.last_victim = Some(victim);
+        if let TieBreak::Jitter(_) = self.tie_break {
+            self.jitter_state = xorshift64(self.jitter_state);
+        }
+        Some(victim)
+    }
 
+    /// Evict every **evictable** frame whose id satisfies `pred`, ignoring LRU-K
+    /// order (this is invalidation, not reclamation).
+    ///
+    /// Returns `(evicted, skipped)`, both sorted by id: `skipped` lists matching
+    /// frames that were left in place because they are not evictable.
+    pub fn evict_all_matching<F: Fn(FrameId) -> bool>(
+        &mut self,
+        pred: F,
+    ) -> (Vec<FrameId>, Vec<FrameId>) {
+        let mut evicted = Vec::new();
+        let mut skipped = Vec::new();
         for (&frame_id, node) in self.node_store.iter() {
-            if !node.is_evictable {
+            if !pred(frame_id) {
                 continue;
             }
+            if node.is_evictable {
+                evicted.push(frame_id);
+            } else {
+                skipped.push(frame_id);
+            }
+        }
+        evicted.sort_unstable();
+        skipped.sort_unstable();
+        for &frame_id in &evicted {
+            self.detach(frame_id);
+            self.emit(ReplacerEvent::Evicted(frame_id));
+        }
+        (evicted, skipped)
+    }
+
+    /// Like [`evict`](Self::evict), but also hand back the victim's history
+    /// (oldest-first) and evictable flag so it can be transplanted into another
+    /// replacer with [`record_access_trace`](Self::record_access_trace).
+    pub fn evict_take(&mut self) -> Option<(FrameId, Vec<u64>, bool)> {
+        let victim = self.peek_victim()?;
+        let node = &self.node_store[&victim];
+        let (history, evictable) = (node.history_vec(), node.is_evictable);
+        self.evict();
+        Some((victim, history, evictable))
+    }
+
+    /// Return the frame [`evict`](Self::evict) would choose, without removing it.
+    pub fn peek_victim(&self) -> Option<FrameId> {
+        // Fast path: a single evictable frame is the victim if it is eligible
+        // at all, so skip building and comparing keys.
+        if self.current_size == 1 {
+            return self
+                .node_store
+                .iter()
+                .find(|(_, node)| node.is_evictable)
+                .filter(|(_, node)| self.is_candidate(node))
+                .map(|(&frame_id, _)| frame_id);
+        }
 
-            // ∞ distance for nodes with < K references.
-            let k_dist = match node.kth_ts() {
-                None => u128::MAX,
-                Some(kth) => (self.current_timestamp as u128).saturating_sub(kth as u128),
-            };
+        let mut best: Option<Key> = None;
 
-            // For tie-breaking we want the most recent access time (older is "better" to evict).
-            let last_ts = node.last_ts().unwrap_or(0);
+        for (&frame_id, node) in self.node_store.iter() {
+            if !self.is_candidate(node) {
+                continue;
+            }
 
-            let key = Key {
-                k_dist,
-                last_ts,
-                frame_id,
-            };
+            let key = self.victim_key(frame_id, node);
 
-            if let Some((cur_key, _)) = best {
+            if let Some(cur_key) = best {
                 if better(key, cur_key) {
-                    best = Some((key, frame_id));
+                    best = Some(key);
                 }
             } else {
-                best = Some((key, frame_id));
+                best = Some(key);
+            }
+        }
+
+        best.map(|key| key.frame_id)
+    }
+
+    /// All frames eligible for eviction, best victim first (the order repeated
+    /// [`evict`](Self::evict) calls would take, absent new accesses).
+    ///
+    /// This is the one eviction order every ranking query follows. Under
+    /// [`TieBreak::RoundRobin`] and [`TieBreak::Jitter`] each eviction moves
+    /// the cursor or generator state, so the order replays those updates one
+    /// victim at a time (O(n²) instead of a sort).
+    pub fn ranked_victims(&self) -> Vec<FrameId> {
+        if self.tie_break == TieBreak::LowestId {
+            let mut keys: Vec<Key> = self
+                .node_store
+                .iter()
+                .filter(|(_, node)| self.is_candidate(node))
+                .map(|(&frame_id, node)| self.victim_key(frame_id, node))
+                .collect();
+            keys.sort_by(|&a, &b| victim_cmp(a, b));
+            return keys.into_iter().map(|key| key.frame_id).collect();
+        }
+        self.replay(self.candidates()).collect()
+    }
+
+    /// Lazily yield eligible frames best victim first, without mutating state.
+    ///
+    /// Same order as [`ranked_victims`](Self::ranked_victims). Under
+    /// [`TieBreak::LowestId`] it is backed by a heap: building it is O(n) and
+    /// each item costs O(log n), so taking only a short prefix of a large pool
+    /// avoids a full sort. Other tie-breaks cost O(n) per item.
+    pub fn victims_ordered(&self) -> impl Iterator<Item = FrameId> + '_ {
+        // Heap for `LowestId`, replay otherwise; the other source stays empty.
+        let lowest_id = self.tie_break == TieBreak::LowestId;
+        let mut heap: BinaryHeap<HeapKey> = self
+            .node_store
+            .iter()
+            .filter(|(_, node)| lowest_id && self.is_candidate(node))
+            .map(|(&frame_id, node)| HeapKey(self.victim_key(frame_id, node)))
+            .collect();
+        let replay = self.replay(if lowest_id {
+            Vec::new()
+        } else {
+            self.candidates()
+        });
+        std::iter::from_fn(move || heap.pop().map(|HeapKey(key)| key.frame_id)).chain(replay)
+    }
+
+    /// Compare two tracked frames in the order of
+    /// [`ranked_victims`](Self::ranked_victims); `Less` means `a` is the
+    /// better victim.
+    ///
+    /// Evictability is not considered: each frame is ranked as if it were
+    /// eligible. Under [`TieBreak::RoundRobin`] and [`TieBreak::Jitter`] this
+    /// replays the ranking (O(n²)). Returns `None` if either frame is untracked.
+    pub fn compare_frames(&self, a: FrameId, b: FrameId) -> Option<Ordering> {
+        let node_a = self.node_store.get(&a)?;
+        let node_b = self.node_store.get(&b)?;
+        if a == b {
+            return Some(Ordering::Equal);
+        }
+        if self.tie_break == TieBreak::LowestId {
+            let key_a = self.victim_key(a, node_a);
+            let key_b = self.victim_key(b, node_b);
+            return Some(victim_cmp(key_a, key_b));
+        }
+
+        let mut frames = self.candidates();
+        for (frame_id, node) in [(a, node_a), (b, node_b)] {
+            if !self.is_candidate(node) {
+                frames.push((frame_id, node));
             }
         }
+        let first = self
+            .replay(frames)
+            .find(|&frame_id| frame_id == a || frame_id == b)
+            .expect("both frames are ranked");
+        Some(if first == a {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        })
+    }
+
+    /// The eligible frame [`evict`](Self::evict) would take first (largest
+    /// K-distance). Same as [`peek_victim`](Self::peek_victim).
+    pub fn coldest(&self) -> Option<FrameId> {
+        self.peek_victim()
+    }
+
+    /// The eligible frame [`evict`](Self::evict) would take last (smallest
+    /// K-distance), i.e. the final entry of [`ranked_victims`](Self::ranked_victims).
+    pub fn hottest(&self) -> Option<FrameId> {
+        if self.tie_break != TieBreak::LowestId {
+            return self.ranked_victims().pop();
+        }
+        self.node_store
+            .iter()
+            .filter(|(_, node)| self.is_candidate(node))
+            .map(|(&frame_id, node)| self.victim_key(frame_id, node))
+            .max_by(|&a, &b| victim_cmp(a, b))
+            .map(|key| key.frame_id)
+    }
+
+    /// Frames eligible for eviction, in arbitrary order.
+    fn candidates(&self) -> Vec<(FrameId, &LRUKNode)> {
+        self.node_store
+            .iter()
+            .filter(|(_, node)| self.is_candidate(node))
+            .map(|(&frame_id, node)| (frame_id, node))
+            .collect()
+    }
+
+    /// Yield `frames` in the order repeated evictions would take them,
+    /// replaying the round-robin cursor and jitter state after each pick.
+    fn replay<'a>(
+        &'a self,
+        mut left: Vec<(FrameId, &'a LRUKNode)>,
+    ) -> impl Iterator<Item = FrameId> + 'a {
+        let (mut last_victim, mut jitter_state) = (self.last_victim, self.jitter_state);
+        std::iter::from_fn(move || {
+            let (i, key) = left
+                .iter()
+                .enumerate()
+                .map(|(i, &(frame_id, node))| {
+                    let key = self.victim_key_with(frame_id, node, last_victim, jitter_state);
+                    (i, key)
+                })
+                .min_by(|&(_, a), &(_, b)| victim_cmp(a, b))?;
+            left.swap_remove(i);
+            last_victim = Some(key.frame_id);
+            if let TieBreak::Jitter(_) = self.tie_break {
+                jitter_state = xorshift64(jitter_state);
+            }
+            Some(key.frame_id)
+        })
+    }
+
+    /// Build the comparable eviction key for a tracked node.
+    fn victim_key(&self, frame_id: FrameId, node: &LRUKNode) -> Key {
+        self.victim_key_with(frame_id, node, self.last_victim, self.jitter_state)
+    }
+
+    /// [`victim_key`](Self::victim_key) with an explicit round-robin cursor
+    /// and jitter state, for ranking ahead of evictions.
+    fn victim_key_with(
+        &self,
+        frame_id: FrameId,
+        node: &LRUKNode,
+        last_victim: Option<FrameId>,
+        jitter_state: u64,
+    ) -> Key {
+        // ∞ distance for nodes with < K references.
+        let k_dist = if node.is_infinite() {
+            u128::MAX
+        } else {
+            let kth = node.kth_ts().expect("finite node has k references");
+            (self.current_timestamp as u128).saturating_sub(kth as u128)
+        };
+
+        let count_rank = if self.rank_infinite_by_count && node.is_infinite() {
+            node.len()
+        } else {
+            0
+        };
+
+        // For tie-breaking we want the most recent access time (older is "better" to evict).
+        let last_ts = node.last_ts().unwrap_or(0);
+
+        // Round-robin: ids after the cursor rank ahead of those at or before it.
+        // Jitter: rank by a hash of the generator state and the id.
+        let tie_rank = match (self.tie_break, last_victim) {
+            (TieBreak::RoundRobin, Some(cursor)) if frame_id <= cursor => (1, frame_id),
+            (TieBreak::Jitter(_), _) => (mix64(jitter_state ^ frame_id as u64), frame_id),
+            _ => (0, frame_id),
+        };
 
-        if let Some((_, victim)) = best {
-            // Remove safely; if this errors it means a logic bug because we only
-            // selected evictable frames above.
-            let _ = self.remove(victim);
-            return Some(victim);
+        Key {
+            class: node.class,
+            k_dist,
+            count_rank,
+            last_ts,
+            tie_rank,
+            frame_id,
         }
-        None
+    }
+
+    /// Backward K-distance of `frame_id` (`now - kth_ts`), or `u64::MAX` if it
+    /// has fewer than `k` references. `None` if the frame is untracked.
+    pub fn k_distance(&self, frame_id: FrameId) -> Option<u64> {
+        let node = self.node_store.get(&frame_id)?;
+        Some(if node.is_infinite() {
+            u64::MAX
+        } else {
+            let kth = node.kth_ts().expect("finite node has k references");
+            self.current_timestamp.saturating_sub(kth)
+        })
+    }
+
+    /// Number of remembered accesses for `frame_id` (≤ k), or `None` if untracked.
+    pub fn access_count(&self, frame_id: FrameId) -> Option<usize> {
+        self.node_store.get(&frame_id).map(LRUKNode::len)
+    }
+
+    /// Whether the next victim has fewer than `k` references (∞ K-distance).
+    ///
+    /// Returns `None` when nothing is evictable.
+    pub fn next_victim_is_infinite(&self) -> Option<bool> {
+        let victim = self.peek_victim()?;
+        self.node_store.get(&victim).map(LRUKNode::is_infinite)
     }
 
     /// Return the number of **evictable** frames.
@@ -251,4 +1330,73 @@ impl LRUKReplacer {
     pub fn size(&self) -> usize {
         self.current_size
     }
+
+    /// Accesses skipped by the most recent [`extend`](Extend::extend) because
+    /// they would have tracked a new frame beyond capacity.
+    #[inline]
+    pub fn last_extend_skipped(&self) -> usize {
+        self.last_extend_skipped
+    }
+
+    /// Return the number of tracked but **non-evictable** frames, in O(1).
+    #[inline]
+    pub fn non_evictable_count(&self) -> usize {
+        self.non_evictable_count
+    }
+
+    /// Compact the history of every node except the `keep_full_window_for`
+    /// hottest ones (least likely to be evicted): windows of three or more
+    /// timestamps are packed as 32-bit offsets from the oldest, and spare
+    /// capacity is released from the rest. Uncompacted nodes pay nothing extra.
+    ///
+    /// Compaction is lossless, so eviction order, [`access_history`] and
+    /// [`dump_histories`] are unaffected, including after later accesses (a
+    /// compacted node unpacks its window on the next access).
+    ///
+    /// [`access_history`]: Self::access_history
+    /// [`dump_histories`]: Self::dump_histories
+    pub fn compact_cold(&mut self, keep_full_window_for: usize) {
+        let mut keys: Vec<Key> = self
+            .node_store
+            .iter()
+            .map(|(&frame_id, node)| self.victim_key(frame_id, node))
+            .collect();
+        // Best victims (coldest) first; the hottest end keeps its full window.
+        keys.sort_by(|&a, &b| victim_cmp(a, b));
+        let cold = keys.len().saturating_sub(keep_full_window_for);
+        for key in &keys[..cold] {
+            if let Some(node) = self.node_store.get_mut(&key.frame_id) {
+                node.compact();
+            }
+        }
+    }
+
+    /// How many more new frames can be tracked before `record_access` on an
+    /// unknown frame fails, so callers can evict proactively.
+    #[inline]
+    pub fn capacity_remaining(&self) -> usize {
+        self.capacity - self.node_store.len()
+    }
+
+    /// Every tracked frame id, evictable or not, in arbitrary order.
+    pub fn frame_ids(&self) -> impl Iterator<Item = FrameId> + '_ {
+        self.node_store.keys().copied()
+    }
+
+    /// Return the remembered access timestamps of `frame_id` (oldest-first),
+    /// or `None` if the frame is not tracked.
+    pub fn access_history(&self, frame_id: FrameId) -> Option<Vec<u64>> {
+        self.node_store.get(&frame_id).map(LRUKNode::history_vec)
+    }
+
+    /// Snapshot the access history of every tracked frame (oldest-first).
+    ///
+    /// Read-only: clones the internal deques. Useful for writing a trace file
+    /// for replay or for feeding external LRU-K simulators.
+    pub fn dump_histories(&self) -> HashMap<FrameId, Vec<u64>> {
+        self.node_store
+            .iter()
+            .map(|(&frame_id, node)| (frame_id, node.history_vec()))
+            .collect()
+    }
 }
@@ -1,3 +1,7 @@
+#[cfg(feature = "adaptive-k")]
+mod adaptive_k;
 mod lru_k_replacer;
+mod simulate;
 
-pub use lru_k_replacer::LRUKReplacer;
+pub use lru_k_replacer::{LRUKReplacer, Pressure, ReplacerEvent, TieBreak};
+pub use simulate::{SimResult, simulate};
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use super::LRUKReplacer;
+
+type FrameId = usize;
+type PageId = usize;
+
+/// Outcome of replaying a page-access trace through [`simulate`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SimResult {
+    /// Accesses to a page that was already buffered.
+    pub hits: usize,
+    /// Accesses that had to bring the page in.
+    pub misses: usize,
+    /// `hits / (hits + misses)`, or 0.0 for an empty trace.
+    pub hit_ratio: f64,
+    /// Misses that had to evict a resident page first.
+    pub evictions: usize,
+}
+
+/// Replay `trace` against a pool of `pool_size` frames managed by an LRU-K
+/// replacer with parameter `k`, and report the hit ratio.
+///
+/// Uses a mock page table instead of a real buffer pool: every page is
+/// unpinned (evictable) as soon as its access completes, and free frames are
+/// handed out before anything is evicted.
+///
+/// # Panics
+/// Panics if `pool_size == 0` or `k == 0`.
+pub fn simulate(trace: &[PageId], pool_size: usize, k: usize) -> SimResult {
+    let mut replacer = LRUKReplacer::new(pool_size, k);
+    let mut page_table: HashMap<PageId, FrameId> = HashMap::with_capacity(pool_size);
+    let mut frame_pages: Vec<Option<PageId>> = vec![None; pool_size];
+    let mut free_frames: Vec<FrameId> = (0..pool_size).rev().collect();
+
+    let (mut hits, mut misses, mut evictions) = (0, 0, 0);
+
+    for &page_id in trace {
+        let frame_id = match page_table.get(&page_id) {
+            Some(&frame_id) => {
+                hits += 1;
+                frame_id
+            }
+            None => {
+                misses += 1;
+                let frame_id = match free_frames.pop() {
+                    Some(frame_id) => frame_id,
+                    None => {
+                        // Every resident page is unpinned, so a victim always exists.
+                        let victim = replacer.evict().expect("all frames are evictable");
+                        let old = frame_pages[victim].take().expect("victim holds a page");
+                        page_table.remove(&old);
+                        evictions += 1;
+                        victim
+                    }
+                };
+                page_table.insert(page_id, frame_id);
+                frame_pages[frame_id] = Some(page_id);
+                frame_id
+            }
+        };
+
+        replacer
+            .access_and_pin(frame_id)
+            .expect("frame ids never exceed pool size");
+        replacer
+            .unpin(frame_id, true)
+            .expect("frame was just pinned");
+    }
+
+    let total = hits + misses;
+    SimResult {
+        hits,
+        misses,
+        hit_ratio: if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        },
+        evictions,
+    }
+}
@@ -1,4 +1,6 @@
-use crate::buffer::LRUKReplacer;
+use std::cmp::Ordering;
+
+use crate::buffer::{LRUKReplacer, Pressure, ReplacerEvent, TieBreak};
 use crate::error::CustomError;
 
 type FrameId = usize;
@@ -36,6 +38,19 @@ fn new_panics_when_capacity_zero() {
     let _ = LRUKReplacer::new(0, 2);
 }
 
+#[test]
+fn try_new_rejects_zero_k_and_capacity() {
+    match LRUKReplacer::try_new(4, 0) {
+        Err(CustomError::Internal(msg)) => assert_eq!(msg, "k must be >= 1"),
+        other => panic!("try_new(4, 0) should fail, got {other:?}"),
+    }
+    match LRUKReplacer::try_new(0, 2) {
+        Err(CustomError::Internal(msg)) => assert_eq!(msg, "capacity must be >= 1"),
+        other => panic!("try_new(0, 2) should fail, got {other:?}"),
+    }
+    assert!(LRUKReplacer::try_new(4, 2).is_ok());
+}
+
 // --- Basic flow and size accounting -------------------------------------
 
 #[test]
@@ -291,3 +306,1203 @@ fn smoke_many_updates_interleaved() {
     assert_eq!(r.evict(), None);
     assert_eq!(count_evictable_scan(&r), 0);
 }
+
+// --- History snapshots ---------------------------------------------------
+
+#[test]
+fn access_history_is_oldest_first_and_bounded_by_k() {
+    let mut r = LRUKReplacer::new(4, 2);
+    assert_eq!(r.access_history(1), None);
+
+    r.record_access(1).unwrap(); // ts 1
+    r.record_access(2).unwrap(); // ts 2
+    r.record_access(1).unwrap(); // ts 3
+    r.record_access(1).unwrap(); // ts 4 (drops ts 1)
+
+    assert_eq!(r.access_history(1), Some(vec![3, 4]));
+    assert_eq!(r.access_history(2), Some(vec![2]));
+}
+
+#[test]
+fn dump_histories_matches_per_frame_history() {
+    let mut r = LRUKReplacer::new(16, 3);
+    for i in 0..10 {
+        for _ in 0..(i % 4) + 1 {
+            r.record_access(i).unwrap();
+        }
+    }
+
+    let dump = r.dump_histories();
+    assert_eq!(dump.len(), 10);
+    for (id, hist) in &dump {
+        assert_eq!(Some(hist.clone()), r.access_history(*id));
+    }
+}
+
+// --- Promotion observer --------------------------------------------------
+
+#[test]
+fn promotion_observer_fires_once_on_kth_access() {
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+
+    let mut r = LRUKReplacer::new(4, 3);
+    r.set_promotion_observer(Box::new(move |id| sink.lock().unwrap().push(id)));
+
+    r.record_access(1).unwrap();
+    r.record_access(1).unwrap();
+    assert!(seen.lock().unwrap().is_empty());
+
+    r.record_access(1).unwrap(); // third access: crosses into finite distance
+    assert_eq!(*seen.lock().unwrap(), vec![1]);
+
+    r.record_access(1).unwrap(); // already finite: no further notification
+    r.record_access(2).unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![1]);
+}
+
+// --- Trace replay --------------------------------------------------------
+
+#[test]
+fn record_access_trace_rejects_backwards_timestamps() {
+    let mut r = LRUKReplacer::new(4, 2);
+    let err = r
+        .record_access_trace(&[(1, 5), (2, 7), (1, 6)])
+        .unwrap_err();
+    match err {
+        CustomError::Internal(s) => assert!(s.contains("index 2")),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    // Validation happens before anything is applied.
+    assert_eq!(r.access_history(1), None);
+}
+
+#[test]
+fn record_access_trace_over_capacity_applies_nothing() {
+    let mut r = LRUKReplacer::new(3, 2);
+    r.record_access(1).unwrap();
+    let before = r.dump_histories();
+
+    // Frames 2 and 3 fit, frame 4 does not; re-accessing 1 or 2 is free.
+    let err = r
+        .record_access_trace(&[(2, 5), (1, 6), (3, 7), (2, 8), (4, 9)])
+        .unwrap_err();
+    match err {
+        CustomError::Internal(s) => assert!(s.contains("index 4")),
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert_eq!(r.dump_histories(), before);
+    assert_eq!(r.now(), 1);
+}
+
+#[test]
+fn replayed_dump_reproduces_eviction_order() {
+    let mut a = LRUKReplacer::new(16, 2);
+    for step in 0..40usize {
+        a.record_access((step * 7) % 11).unwrap();
+    }
+
+    // Flatten the dump back into a time-ordered trace.
+    let mut trace: Vec<(FrameId, u64)> = a
+        .dump_histories()
+        .into_iter()
+        .flat_map(|(id, hist)| hist.into_iter().map(move |ts| (id, ts)))
+        .collect();
+    trace.sort_by_key(|&(_, ts)| ts);
+
+    let mut b = LRUKReplacer::new(16, 2);
+    b.record_access_trace(&trace).unwrap();
+
+    for id in 0..11 {
+        a.set_evictable(id, true).unwrap();
+        b.set_evictable(id, true).unwrap();
+    }
+    assert_eq!(evict_n(&mut a, 11), evict_n(&mut b, 11));
+}
+
+// --- Peeking at the next victim ------------------------------------------
+
+#[test]
+fn peek_victim_does_not_remove() {
+    let mut r = LRUKReplacer::new(4, 2);
+    r.record_access(1).unwrap();
+    r.record_access(2).unwrap();
+    r.set_evictable(1, true).unwrap();
+    r.set_evictable(2, true).unwrap();
+
+    assert_eq!(r.peek_victim(), Some(1));
+    assert_eq!(r.size(), 2);
+    assert_eq!(r.evict(), Some(1));
+}
+
+#[test]
+fn next_victim_is_infinite_cold_and_warm() {
+    let mut r = LRUKReplacer::new(4, 2);
+    assert_eq!(r.next_victim_is_infinite(), None);
+
+    // Warm frame only: victim has k references.
+    r.record_access(1).unwrap();
+    r.record_access(1).unwrap();
+    r.set_evictable(1, true).unwrap();
+    assert_eq!(r.access_count(1), Some(2));
+    assert_eq!(r.next_victim_is_infinite(), Some(false));
+
+    // A cold frame jumps ahead of the warm one.
+    r.record_access(2).unwrap();
+    r.set_evictable(2, true).unwrap();
+    assert_eq!(r.peek_victim(), Some(2));
+    assert_eq!(r.next_victim_is_infinite(), Some(true));
+}
+
+// --- Tiebreak modes ------------------------------------------------------
+
+/// Admit `ids` all at the same logical timestamp so they are genuinely tied.
+fn admit_tied(r: &mut LRUKReplacer, ids: &[FrameId], ts: u64) {
+    for &id in ids {
+        r.record_access_at(id, ts).unwrap();
+        r.set_evictable(id, true).unwrap();
+    }
+}
+
+#[test]
+fn lowest_id_tiebreak_always_picks_smallest() {
+    let mut r = LRUKReplacer::new(8, 2);
+    admit_tied(&mut r, &[0, 1, 2, 3], 10);
+
+    let mut victims = Vec::new();
+    for _ in 0..4 {
+        let v = r.evict().unwrap();
+        victims.push(v);
+        admit_tied(&mut r, &[v], 10);
+    }
+    assert_eq!(victims, vec![0, 0, 0, 0]);
+}
+
+#[test]
+fn round_robin_tiebreak_spreads_evictions() {
+    let mut r = LRUKReplacer::new(8, 2);
+    r.set_tie_break(TieBreak::RoundRobin);
+    admit_tied(&mut r, &[0, 1, 2, 3], 10);
+
+    let mut victims = Vec::new();
+    for _ in 0..6 {
+        let v = r.evict().unwrap();
+        victims.push(v);
+        admit_tied(&mut r, &[v], 10);
+    }
+    assert_eq!(victims, vec![0, 1, 2, 3, 0, 1]);
+}
+
+#[test]
+fn round_robin_ranking_matches_repeated_evict() {
+    let mut r = LRUKReplacer::new(8, 2);
+    r.set_tie_break(TieBreak::RoundRobin);
+    // 0, 1, 3, 4 tie with a finite distance; 2 has a single access (∞).
+    admit_tied(&mut r, &[0, 1, 3, 4], 10);
+    admit_tied(&mut r, &[0, 1, 2, 3, 4], 10);
+
+    // Evicting 2 moves the cursor, so the tied frames after it go first.
+    let ranked = r.ranked_victims();
+    assert_eq!(ranked, vec![2, 3, 4, 0, 1]);
+    assert_eq!(r.compare_frames(0, 3), Some(Ordering::Greater));
+    assert_eq!(evict_n(&mut r, 5), ranked);
+}
+
+// --- k = 1 degenerates to plain LRU --------------------------------------
+
+#[test]
+fn k1_single_access_is_never_infinite() {
+    let mut r = LRUKReplacer::new(8, 1);
+    for id in 0..4 {
+        r.record_access(id).unwrap();
+        r.set_evictable(id, true).unwrap();
+        assert_eq!(r.access_count(id), Some(1));
+    }
+    while r.size() > 0 {
+        assert_eq!(r.next_victim_is_infinite(), Some(false));
+        r.evict();
+    }
+}
+
+#[test]
+fn k1_evicts_least_recently_accessed() {
+    let mut r = LRUKReplacer::new(8, 1);
+    for id in 0..5 {
+        r.record_access(id).unwrap();
+        r.set_evictable(id, true).unwrap();
+    }
+    // Re-touch all but 2; least-recent first is now 2, 3, 0, 4, 1.
+    for id in [3, 0, 4, 1] {
+        r.record_access(id).unwrap();
+    }
+    assert_eq!(evict_n(&mut r, 5), vec![2, 3, 0, 4, 1]);
+}
+
+// --- Compacting cold histories -------------------------------------------
+
+#[test]
+fn compact_cold_preserves_eviction_order() {
+    let build = || {
+        let mut r = LRUKReplacer::new(32, 4);
+        for step in 0..120usize {
+            r.record_access((step * 5 + step / 7) % 13).unwrap();
+        }
+        for id in 0..13 {
+            r.set_evictable(id, true).unwrap();
+        }
+        r
+    };
+
+    let mut plain = build();
+    let mut compacted = build();
+    compacted.compact_cold(3);
+
+    assert_eq!(plain.dump_histories(), compacted.dump_histories());
+    assert_eq!(evict_n(&mut plain, 13), evict_n(&mut compacted, 13));
+}
+
+#[test]
+fn compact_cold_preserves_eviction_order_after_more_accesses() {
+    let build = || {
+        let mut r = LRUKReplacer::new(32, 4);
+        for step in 0..120usize {
+            r.record_access((step * 5 + step / 7) % 13).unwrap();
+        }
+        r
+    };
+
+    let mut plain = build();
+    let mut compacted = build();
+    compacted.compact_cold(3);
+
+    // Re-access compacted and uncompacted frames alike.
+    for r in [&mut plain, &mut compacted] {
+        for step in 0..30usize {
+            r.record_access((step * 7) % 13).unwrap();
+        }
+        for id in 0..13 {
+            r.set_evictable(id, true).unwrap();
+        }
+    }
+
+    assert_eq!(plain.dump_histories(), compacted.dump_histories());
+    assert_eq!(evict_n(&mut plain, 13), evict_n(&mut compacted, 13));
+}
+
+#[test]
+fn compaction_shrinks_history_memory() {
+    let mut r = LRUKReplacer::new(8, 8);
+    for _ in 0..8 {
+        r.record_access(1).unwrap();
+    }
+    r.record_access(2).unwrap();
+    r.record_access(2).unwrap();
+    let full = r.node_store[&1].history_heap_bytes();
+    let short = r.node_store[&2].history_heap_bytes();
+
+    r.compact_cold(0);
+    // Eight timestamps: 64 bytes down to 2 + 7 packed 32-bit words.
+    assert_eq!(full, 64);
+    assert_eq!(r.node_store[&1].history_heap_bytes(), 36);
+    // Two timestamps are not worth packing; only spare capacity is released.
+    assert!(r.node_store[&2].history_heap_bytes() <= short);
+    assert_eq!(r.access_history(2).map(|h| h.len()), Some(2));
+}
+
+#[test]
+fn compacted_node_regrows_on_access() {
+    let mut r = LRUKReplacer::new(4, 4);
+    for _ in 0..4 {
+        r.record_access(1).unwrap(); // ts 1..=4
+    }
+    r.compact_cold(0);
+    assert_eq!(r.access_history(1), Some(vec![1, 2, 3, 4]));
+    assert_eq!(r.access_count(1), Some(4));
+
+    r.record_access(1).unwrap(); // ts 5
+    assert_eq!(r.access_history(1), Some(vec![2, 3, 4, 5]));
+    assert_eq!(r.k_distance(1), Some(3));
+}
+
+// --- Targeted invalidation -----------------------------------------------
+
+#[test]
+fn evict_all_matching_removes_only_evictable_matches() {
+    let mut r = LRUKReplacer::new(16, 2);
+    for id in 0..10 {
+        r.record_access(id).unwrap();
+        r.set_evictable(id, true).unwrap();
+    }
+    // Frame 4 is pinned: it matches but must stay.
+    r.set_evictable(4, false).unwrap();
+
+    let (evicted, skipped) = r.evict_all_matching(|id| (3..7).contains(&id));
+    assert_eq!(evicted, vec![3, 5, 6]);
+    assert_eq!(skipped, vec![4]);
+
+    assert_eq!(r.size(), 6);
+    assert_eq!(r.access_count(4), Some(1));
+    for id in [0, 1, 2, 7, 8, 9] {
+        assert_eq!(r.access_count(id), Some(1));
+    }
+    for id in [3, 5, 6] {
+        assert_eq!(r.access_count(id), None);
+    }
+}
+
+// --- Back-pressure -------------------------------------------------------
+
+#[test]
+fn pressure_tracks_evictable_count() {
+    let mut r = LRUKReplacer::new(8, 2);
+    r.set_low_watermark(3);
+    assert_eq!(r.pressure(), Pressure::Ok);
+
+    for id in 0..4 {
+        r.record_access(id).unwrap();
+    }
+    assert_eq!(r.pressure(), Pressure::Critical);
+
+    r.set_evictable(0, true).unwrap();
+    assert_eq!(r.pressure(), Pressure::Warning);
+
+    for id in 1..4 {
+        r.set_evictable(id, true).unwrap();
+    }
+    assert_eq!(r.pressure(), Pressure::Ok);
+}
+
+// --- Ranking and comparison ----------------------------------------------
+
+#[test]
+fn compare_frames_agrees_with_ranked_victims() {
+    for tie_break in [
+        TieBreak::LowestId,
+        TieBreak::RoundRobin,
+        TieBreak::Jitter(7),
+    ] {
+        let mut r = LRUKReplacer::new(32, 3);
+        r.set_tie_break(tie_break);
+        for step in 0..90usize {
+            r.record_access((step * 11 + step / 5) % 17).unwrap();
+        }
+        // A tied ∞ group, preceded by a lone older ∞ frame in its middle.
+        r.record_access_at(22, 95).unwrap();
+        r.set_evictable(22, true).unwrap();
+        admit_tied(&mut r, &[20, 21, 23, 24, 25], 100);
+        for id in 0..17 {
+            r.set_evictable(id, true).unwrap();
+        }
+
+        let ranked = r.ranked_victims();
+        assert_eq!(ranked.len(), 23);
+        if tie_break == TieBreak::RoundRobin {
+            // Evicting 22 moves the cursor past 20 and 21.
+            assert_eq!(ranked[..6], [22, 23, 24, 25, 20, 21]);
+        }
+        for pair in ranked.windows(2) {
+            assert_eq!(r.compare_frames(pair[0], pair[1]), Some(Ordering::Less));
+            assert_eq!(r.compare_frames(pair[1], pair[0]), Some(Ordering::Greater));
+        }
+        assert_eq!(
+            r.compare_frames(ranked[0], ranked[0]),
+            Some(Ordering::Equal)
+        );
+        assert_eq!(r.compare_frames(ranked[0], 999), None);
+        assert_eq!(r.hottest(), ranked.last().copied());
+
+        assert_eq!(evict_n(&mut r, 23), ranked, "{tie_break:?}");
+    }
+}
+
+// --- Bulk evictability ---------------------------------------------------
+
+#[test]
+fn set_evictable_many_reports_missing_and_counts_found() {
+    let mut r = LRUKReplacer::new(8, 2);
+    for id in 0..4 {
+        r.record_access(id).unwrap();
+    }
+    r.set_evictable(0, true).unwrap();
+
+    // 0 is already evictable (no transition); 1 and 2 flip; 7 and 9 are unknown.
+    let missing = r.set_evictable_many(&[0, 1, 7, 2, 9], true).unwrap();
+    assert_eq!(missing, vec![7, 9]);
+    assert_eq!(r.size(), 3);
+
+    let missing = r.set_evictable_many(&[1, 2, 3, 8], false).unwrap();
+    assert_eq!(missing, vec![8]);
+    assert_eq!(r.size(), 1);
+    assert_eq!(r.size(), count_evictable_scan(&r));
+}
+
+// --- Adaptive k (experimental) -------------------------------------------
+
+#[cfg(feature = "adaptive-k")]
+#[test]
+fn adaptive_k_rejects_bad_range() {
+    let mut r = LRUKReplacer::new(8, 2);
+    assert!(r.enable_adaptive_k(0, 4, 8).is_err());
+    assert!(r.enable_adaptive_k(3, 2, 8).is_err());
+    assert!(r.enable_adaptive_k(1, 4, 0).is_err());
+    assert_eq!(r.k(), 2);
+}
+
+#[cfg(feature = "adaptive-k")]
+#[test]
+fn adaptive_k_converges_for_known_reuse_distance() {
+    // Cycling over 16 frames gives every re-access a reuse distance of 16;
+    // with capacity 64 the target is 64 / 16 = 4.
+    let mut r = LRUKReplacer::new(64, 2);
+    r.enable_adaptive_k(1, 8, 16).unwrap();
+    for step in 0..1000usize {
+        r.record_access(step % 16).unwrap();
+    }
+    assert_eq!(r.k(), 4);
+    assert_eq!(r.access_count(0), Some(4));
+
+    // A much tighter loop (distance 2) pushes k up to the ceiling.
+    for step in 0..1000usize {
+        r.record_access(step % 2).unwrap();
+    }
+    assert_eq!(r.k(), 8);
+
+    // Wide loops (distance 64) bring it back down to the floor.
+    for step in 0..5000usize {
+        r.record_access(step % 64).unwrap();
+    }
+    assert_eq!(r.k(), 1);
+}
+
+// --- Transplanting history -----------------------------------------------
+
+#[test]
+fn evict_take_history_reinserts_with_same_k_distance() {
+    let mut a = LRUKReplacer::new(8, 2);
+    a.record_access(2).unwrap();
+    a.record_access(1).unwrap();
+    a.record_access(2).unwrap();
+    a.record_access(1).unwrap();
+    a.set_evictable(1, true).unwrap();
+
+    let before = a.k_distance(1).unwrap();
+    assert_ne!(before, u64::MAX);
+
+    let (victim, history, evictable) = a.evict_take().unwrap();
+    assert_eq!(victim, 1);
+    assert_eq!(history, vec![2, 4]);
+    assert!(evictable);
+    assert_eq!(a.k_distance(1), None);
+
+    let mut b = LRUKReplacer::new(8, 2);
+    let trace: Vec<(FrameId, u64)> = history.iter().map(|&ts| (victim, ts)).collect();
+    b.record_access_trace(&trace).unwrap();
+    b.set_evictable(victim, evictable).unwrap();
+    assert_eq!(b.k_distance(victim), Some(before));
+}
+
+// --- Minimum residency ---------------------------------------------------
+
+#[test]
+fn min_residency_passes_over_fresh_frames() {
+    let mut r = LRUKReplacer::new(8, 2);
+    r.set_min_residency(3);
+
+    // Frame 0: warm (finite distance), admitted early.
+    r.record_access(0).unwrap(); // ts 1
+    r.record_access(0).unwrap(); // ts 2
+    r.set_evictable(0, true).unwrap();
+    // Frame 9: pinned filler to advance the clock.
+    r.record_access(9).unwrap(); // ts 3
+    r.record_access(9).unwrap(); // ts 4
+    // Frame 1: cold (infinite), would normally be the victim.
+    r.record_access(1).unwrap(); // ts 5
+    r.set_evictable(1, true).unwrap();
+
+    assert_eq!(r.size(), 2);
+    assert_eq!(r.peek_victim(), Some(0));
+    assert_eq!(r.evict(), Some(0));
+    assert_eq!(r.evict(), None);
+    assert_eq!(r.size(), 1);
+
+    for _ in 0..3 {
+        r.record_access(9).unwrap(); // ts 6..=8
+    }
+    assert_eq!(r.evict(), Some(1));
+}
+
+// --- Ranking infinite frames by access count -----------------------------
+
+#[test]
+fn rank_infinite_by_count_prefers_fewer_references() {
+    let build = || {
+        let mut r = LRUKReplacer::new(8, 3);
+        // A: two references, older; B: one reference, newer. Both infinite.
+        r.record_access(1).unwrap();
+        r.record_access(1).unwrap();
+        r.record_access(2).unwrap();
+        r.set_evictable(1, true).unwrap();
+        r.set_evictable(2, true).unwrap();
+        r
+    };
+
+    let mut plain = build();
+    assert_eq!(evict_n(&mut plain, 2), vec![1, 2]);
+
+    let mut by_count = build();
+    by_count.set_rank_infinite_by_count(true);
+    assert_eq!(evict_n(&mut by_count, 2), vec![2, 1]);
+}
+
+// --- Cloning -------------------------------------------------------------
+
+#[test]
+fn clone_is_independent() {
+    let mut r = LRUKReplacer::new(16, 2);
+    for step in 0..30usize {
+        r.record_access((step * 3) % 7).unwrap();
+    }
+    for id in 0..7 {
+        r.set_evictable(id, true).unwrap();
+    }
+    let order = r.ranked_victims();
+
+    let mut fork = r.clone();
+    assert_eq!(evict_n(&mut fork, 3), order[..3].to_vec());
+    fork.record_access(100).unwrap();
+    assert_eq!(fork.size(), 4);
+
+    assert_eq!(r.size(), 7);
+    assert_eq!(r.access_count(100), None);
+    assert_eq!(evict_n(&mut r, 7), order);
+}
+
+// --- Priority classes ----------------------------------------------------
+
+#[test]
+fn lower_class_is_exhausted_before_higher() {
+    let mut r = LRUKReplacer::new(16, 2);
+    // Class 1 frames are the coldest by recency; class 0 frames are hot.
+    for id in [10, 11, 12] {
+        r.record_access(id).unwrap();
+    }
+    for id in [0, 1, 2] {
+        r.record_access(id).unwrap();
+        r.record_access(id).unwrap();
+    }
+    for id in [0, 1, 2, 10, 11, 12] {
+        r.set_evictable(id, true).unwrap();
+    }
+    for id in [10, 11, 12] {
+        r.set_class(id, 1).unwrap();
+    }
+    assert!(r.set_class(99, 1).is_err());
+
+    assert_eq!(evict_n(&mut r, 6), vec![0, 1, 2, 10, 11, 12]);
+}
+
+// --- Event stream --------------------------------------------------------
+
+#[test]
+fn subscribe_receives_exact_event_sequence() {
+    let mut r = LRUKReplacer::new(8, 2);
+    let rx = r.subscribe();
+
+    r.record_access(1).unwrap();
+    r.record_access(2).unwrap();
+    r.set_evictable(1, true).unwrap();
+    r.set_evictable(1, true).unwrap(); // no change: no event
+    r.set_evictable(2, true).unwrap();
+    r.evict().unwrap();
+    r.remove(2).unwrap();
+    r.remove(2).unwrap(); // already gone: no event
+
+    let events: Vec<ReplacerEvent> = rx.try_iter().collect();
+    assert_eq!(
+        events,
+        vec![
+            ReplacerEvent::Accessed(1),
+            ReplacerEvent::Accessed(2),
+            ReplacerEvent::EvictabilityChanged(1, true),
+            ReplacerEvent::EvictabilityChanged(2, true),
+            ReplacerEvent::Evicted(1),
+            ReplacerEvent::Removed(2),
+        ]
+    );
+
+    // Dropping the receiver unsubscribes; further mutations still work.
+    drop(rx);
+    r.record_access(3).unwrap();
+}
+
+// --- Tracked frame ids ---------------------------------------------------
+
+#[test]
+fn frame_ids_includes_non_evictable() {
+    let mut r = LRUKReplacer::new(8, 2);
+    for id in [4, 1, 7] {
+        r.record_access(id).unwrap();
+    }
+    r.record_access(1).unwrap();
+    r.set_evictable(7, true).unwrap();
+
+    assert_eq!(r.frame_ids().count(), 3);
+    let mut ids: Vec<FrameId> = r.frame_ids().collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![1, 4, 7]);
+
+    r.evict().unwrap();
+    assert_eq!(r.frame_ids().count(), 2);
+}
+
+// --- Freeze / thaw -------------------------------------------------------
+
+#[test]
+fn freeze_blocks_eviction_and_thaw_restores() {
+    let mut r = LRUKReplacer::new(8, 2);
+    for id in 0..5 {
+        r.record_access(id).unwrap();
+    }
+    for id in [0, 2, 4] {
+        r.set_evictable(id, true).unwrap();
+    }
+    let order = r.ranked_victims();
+
+    r.freeze();
+    assert_eq!(r.size(), 0);
+    assert_eq!(r.pressure(), Pressure::Critical);
+    assert_eq!(r.evict(), None);
+
+    // Even frames unpinned during the freeze are not evicted.
+    r.set_evictable(1, true).unwrap();
+    assert_eq!(r.evict(), None);
+    r.set_evictable(1, false).unwrap();
+
+    r.thaw();
+    assert_eq!(r.size(), 3);
+    assert_eq!(evict_n(&mut r, 5), order);
+}
+
+#[test]
+fn frame_pinned_during_freeze_stays_pinned_after_thaw() {
+    let mut r = LRUKReplacer::new(4, 2);
+    r.record_access(1).unwrap();
+    r.record_access(2).unwrap();
+    r.set_evictable(1, true).unwrap();
+    r.set_evictable(2, true).unwrap();
+    let events = r.subscribe();
+
+    r.freeze();
+    r.access_and_pin(1).unwrap();
+    assert_eq!(r.size(), 0);
+    r.thaw();
+
+    // Only 2 is restored; the pin taken during the freeze wins for 1.
+    assert_eq!(r.size(), 1);
+    assert_eq!(r.evict(), Some(2));
+    assert_eq!(r.evict(), None);
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![
+            ReplacerEvent::EvictabilityChanged(1, false),
+            ReplacerEvent::EvictabilityChanged(2, false),
+            ReplacerEvent::Accessed(1),
+            ReplacerEvent::EvictabilityChanged(2, true),
+            ReplacerEvent::Evicted(2),
+        ]
+    );
+}
+
+#[test]
+fn victims_ordered_matches_ranking_and_repeated_peeks() {
+    let mut r = LRUKReplacer::new(64, 3);
+    for step in 0..200usize {
+        r.record_access((step * 13 + step / 3) % 37).unwrap();
+    }
+    for id in (0..37).filter(|id| id % 3 != 0) {
+        r.set_evictable(id, true).unwrap();
+    }
+
+    let lazy: Vec<FrameId> = r.victims_ordered().collect();
+    assert_eq!(lazy, r.ranked_victims());
+
+    // The prefix matches peeking after hypothetically removing each victim.
+    let mut probe = r.clone();
+    for expected in r.victims_ordered().take(5) {
+        assert_eq!(probe.peek_victim(), Some(expected));
+        probe.evict();
+    }
+    // Nothing was removed from the original.
+    assert_eq!(r.size(), lazy.len());
+}
+
+#[test]
+fn victims_ordered_replays_round_robin_and_jitter() {
+    for tie_break in [TieBreak::RoundRobin, TieBreak::Jitter(3)] {
+        let mut r = LRUKReplacer::new(16, 2);
+        r.set_tie_break(tie_break);
+        r.record_access_at(2, 5).unwrap();
+        r.set_evictable(2, true).unwrap();
+        admit_tied(&mut r, &[0, 1, 3, 4, 5, 6], 10);
+
+        let lazy: Vec<FrameId> = r.victims_ordered().collect();
+        assert_eq!(lazy, r.ranked_victims(), "{tie_break:?}");
+        if tie_break == TieBreak::RoundRobin {
+            assert_eq!(lazy, vec![2, 3, 4, 5, 6, 0, 1]);
+        }
+        assert_eq!(evict_n(&mut r, 7), lazy, "{tie_break:?}");
+    }
+}
+
+// --- set_evictable returns the prior state -------------------------------
+
+#[test]
+fn set_evictable_returns_previous_state() {
+    let mut r = LRUKReplacer::new(4, 2);
+    r.record_access(1).unwrap();
+
+    assert!(!r.set_evictable(1, true).unwrap()); // false -> true
+    assert!(r.set_evictable(1, true).unwrap()); // true -> true
+    assert_eq!(r.size(), 1);
+    assert!(r.set_evictable(1, false).unwrap()); // true -> false
+    assert_eq!(r.size(), 0);
+    assert!(r.set_evictable(2, true).is_err());
+}
+
+// --- Remaining capacity --------------------------------------------------
+
+#[test]
+fn capacity_remaining_follows_tracked_frames() {
+    let mut r = LRUKReplacer::new(3, 2);
+    assert_eq!(r.capacity_remaining(), 3);
+
+    r.record_access(1).unwrap();
+    r.record_access(1).unwrap(); // re-access doesn't consume capacity
+    assert_eq!(r.capacity_remaining(), 2);
+    r.record_access(2).unwrap();
+    r.record_access(3).unwrap();
+    assert_eq!(r.capacity_remaining(), 0);
+    assert!(r.record_access(4).is_err());
+
+    r.set_evictable(2, true).unwrap();
+    r.remove(2).unwrap();
+    assert_eq!(r.capacity_remaining(), 1);
+}
+
+fn jittered_sequence(seed: u64) -> Vec<FrameId> {
+    let mut r = LRUKReplacer::new(16, 2).with_jitter(seed);
+    admit_tied(&mut r, &[0, 1, 2, 3, 4, 5, 6, 7], 10);
+    let mut victims = Vec::new();
+    for _ in 0..16 {
+        let v = r.evict().unwrap();
+        victims.push(v);
+        admit_tied(&mut r, &[v], 10);
+    }
+    victims
+}
+
+#[test]
+fn jitter_is_reproducible_per_seed() {
+    assert_eq!(jittered_sequence(42), jittered_sequence(42));
+    assert_ne!(jittered_sequence(1), jittered_sequence(2));
+    // Ties no longer collapse onto the smallest id.
+    assert!(jittered_sequence(42).iter().any(|&v| v != 0));
+}
+
+#[test]
+fn jitter_only_affects_ties() {
+    let mut r = LRUKReplacer::new(8, 2).with_jitter(7);
+    for id in 0..5 {
+        r.record_access(id).unwrap();
+        r.set_evictable(id, true).unwrap();
+    }
+    assert_eq!(evict_n(&mut r, 5), vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn jitter_ranking_matches_repeated_evict() {
+    let mut r = LRUKReplacer::new(16, 2).with_jitter(42);
+    admit_tied(&mut r, &[0, 1, 2, 3, 4, 5, 6, 7], 10);
+    let ranked = r.ranked_victims();
+    assert_eq!(evict_n(&mut r, 8), ranked);
+}
+
+// --- Reconfiguring k -----------------------------------------------------
+
+#[test]
+fn set_k_shrinks_windows_and_recomputes_distances() {
+    let mut r = LRUKReplacer::new(8, 3);
+    for id in [1, 2, 1, 2, 1, 2] {
+        r.record_access(id).unwrap(); // 1 @ 1,3,5; 2 @ 2,4,6
+    }
+    r.record_access(3).unwrap(); // ts 7, single access
+    assert_eq!(r.k_distance(1), Some(6)); // 7 - 1
+    assert_eq!(r.k_distance(3), Some(u64::MAX));
+
+    assert!(r.set_k(0).is_err());
+    r.set_k(2).unwrap();
+    assert_eq!(r.k(), 2);
+    assert_eq!(r.access_history(1), Some(vec![3, 5]));
+    assert_eq!(r.k_distance(1), Some(4)); // 7 - 3
+    assert_eq!(r.k_distance(2), Some(3)); // 7 - 4
+
+    // Growing leaves histories alone; nodes are infinite until they catch up.
+    r.set_k(4).unwrap();
+    assert_eq!(r.access_history(2), Some(vec![4, 6]));
+    assert_eq!(r.k_distance(2), Some(u64::MAX));
+    r.record_access(2).unwrap();
+    r.record_access(2).unwrap();
+    assert_eq!(r.access_history(2), Some(vec![4, 6, 8, 9]));
+    assert_eq!(r.k_distance(2), Some(5));
+}
+
+// --- Non-evictable accounting --------------------------------------------
+
+#[test]
+fn size_and_non_evictable_count_sum_to_tracked() {
+    let mut r = LRUKReplacer::new(16, 2);
+    let check = |r: &LRUKReplacer| {
+        assert_eq!(r.size() + r.non_evictable_count(), r.frame_ids().count());
+    };
+
+    for id in 0..8 {
+        r.record_access(id).unwrap();
+        check(&r);
+    }
+    assert_eq!(r.non_evictable_count(), 8);
+
+    for id in (0..8).step_by(2) {
+        r.set_evictable(id, true).unwrap();
+        check(&r);
+    }
+    r.set_evictable(0, true).unwrap(); // no transition
+    assert_eq!(r.non_evictable_count(), 4);
+
+    r.evict().unwrap();
+    check(&r);
+    r.remove(2).unwrap();
+    check(&r);
+    r.set_evictable(1, true).unwrap();
+    check(&r);
+    r.record_access(1).unwrap(); // re-access: not a new node
+    check(&r);
+
+    assert_eq!(r.size(), 3);
+    assert_eq!(r.non_evictable_count(), 3);
+}
+
+// --- Warming from aggregate counts ---------------------------------------
+
+#[test]
+fn record_access_counts_fills_history_up_to_k() {
+    let mut r = LRUKReplacer::new(8, 3);
+    r.record_access_counts(&[(1, 1000), (2, 2), (3, 0)])
+        .unwrap();
+
+    // Capped at k: three ticks for frame 1, two for frame 2.
+    assert_eq!(r.access_history(1), Some(vec![1, 2, 3]));
+    assert_ne!(r.k_distance(1), Some(u64::MAX));
+    assert_eq!(r.access_history(2), Some(vec![4, 5]));
+    assert_eq!(r.k_distance(2), Some(u64::MAX));
+    assert_eq!(r.access_count(3), None);
+}
+
+// --- Promotion to hot ----------------------------------------------------
+
+#[test]
+fn promote_makes_frame_last_victim() {
+    let mut r = LRUKReplacer::new(8, 2);
+    r.record_access(1).unwrap(); // cold: one access, oldest
+    for id in [2, 3, 2, 3] {
+        r.record_access(id).unwrap(); // warm, finite
+    }
+    for id in [1, 2, 3] {
+        r.set_evictable(id, true).unwrap();
+    }
+    assert_eq!(r.peek_victim(), Some(1));
+
+    r.promote(1).unwrap();
+    assert_eq!(r.k_distance(1), Some(0));
+    assert_eq!(r.access_history(1), Some(vec![5, 5]));
+    assert_eq!(r.size(), 3);
+    assert_eq!(evict_n(&mut r, 3), vec![2, 3, 1]);
+
+    assert!(r.promote(42).is_err());
+}
+
+#[test]
+fn promote_notifies_observer_and_subscribers() {
+    use std::sync::{Arc, Mutex};
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+
+    let mut r = LRUKReplacer::new(4, 3);
+    r.set_promotion_observer(Box::new(move |id| sink.lock().unwrap().push(id)));
+    r.record_access(1).unwrap();
+    let events = r.subscribe();
+
+    r.promote(1).unwrap(); // ∞ -> finite
+    assert_eq!(*seen.lock().unwrap(), vec![1]);
+
+    r.promote(1).unwrap(); // already finite: no further notification
+    assert_eq!(*seen.lock().unwrap(), vec![1]);
+
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![ReplacerEvent::Accessed(1), ReplacerEvent::Accessed(1)]
+    );
+}
+
+// --- Pin / unpin convenience ---------------------------------------------
+
+#[test]
+fn access_and_pin_tracks_frame_as_non_evictable() {
+    let mut r = LRUKReplacer::new(2, 2);
+    r.access_and_pin(1).unwrap();
+    assert_eq!(r.access_count(1), Some(1));
+    assert_eq!(r.size(), 0);
+
+    r.unpin(1, true).unwrap();
+    assert_eq!(r.size(), 1);
+
+    // Re-fetching an unpinned frame pins it again.
+    r.access_and_pin(1).unwrap();
+    assert_eq!(r.access_count(1), Some(2));
+    assert_eq!(r.size(), 0);
+    assert_eq!(r.evict(), None);
+
+    r.access_and_pin(2).unwrap();
+    assert!(r.access_and_pin(3).is_err());
+    assert_eq!(r.access_count(3), None);
+    assert!(r.unpin(3, true).is_err());
+}
+
+#[test]
+fn failed_access_and_pin_leaves_clock_alone() {
+    let mut r = LRUKReplacer::new(1, 2);
+    r.access_and_pin(1).unwrap();
+    let before = r.now();
+
+    assert!(r.access_and_pin(2).is_err());
+    assert_eq!(r.now(), before);
+
+    // The next successful access takes the very next timestamp.
+    r.record_access(1).unwrap();
+    assert_eq!(r.access_history(1), Some(vec![before, before + 1]));
+}
+
+// --- Extend from a trace -------------------------------------------------
+
+#[test]
+fn extend_skips_new_frames_beyond_capacity() {
+    let mut r = LRUKReplacer::new(3, 2);
+    r.extend([1, 2, 3, 4, 1, 5, 2]);
+
+    // 4 and 5 didn't fit; 1 and 2 were still touched twice.
+    assert_eq!(r.last_extend_skipped(), 2);
+    assert_eq!(r.frame_ids().count(), 3);
+    assert_eq!(r.access_count(1), Some(2));
+    assert_eq!(r.access_count(2), Some(2));
+    assert_eq!(r.access_count(4), None);
+
+    // The counter resets on each call.
+    r.extend(vec![3]);
+    assert_eq!(r.last_extend_skipped(), 0);
+    assert_eq!(r.access_count(3), Some(2));
+}
+
+// --- Thread-safety contract ----------------------------------------------
+
+#[test]
+fn replacer_is_send_and_mutex_shareable() {
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    // Fails to compile if a non-`Send` field (e.g. an `Rc`) sneaks in.
+    assert_send::<LRUKReplacer>();
+    assert_send::<ReplacerEvent>();
+    // The supported way to share one replacer across threads.
+    assert_send::<std::sync::Mutex<LRUKReplacer>>();
+    assert_sync::<std::sync::Mutex<LRUKReplacer>>();
+}
+
+// --- Retain by predicate -------------------------------------------------
+
+#[test]
+fn retain_drops_frames_regardless_of_evictability() {
+    let mut r = LRUKReplacer::new(16, 2);
+    for id in 0..10 {
+        r.record_access(id).unwrap();
+        // Evictable: 0, 1, 2, 3, 4; pinned: 5..10.
+        r.set_evictable(id, id < 5).unwrap();
+    }
+
+    r.retain(|id, _| id % 2 == 0);
+
+    let mut ids: Vec<FrameId> = r.frame_ids().collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![0, 2, 4, 6, 8]);
+    assert_eq!(r.size(), 3);
+    assert_eq!(r.non_evictable_count(), 2);
+    assert_eq!(r.size(), count_evictable_scan(&r));
+
+    // The node is visible to the predicate.
+    r.retain(|_, node| node.is_evictable);
+    assert_eq!(r.frame_ids().count(), 3);
+}
+
+// --- Hottest / coldest ---------------------------------------------------
+
+#[test]
+fn hottest_and_coldest_follow_k_distance() {
+    let mut r = LRUKReplacer::new(8, 2);
+    assert_eq!((r.hottest(), r.coldest()), (None, None));
+
+    // 1: finite, oldest kth; 2: finite, newest kth; 3: infinite.
+    for id in [1, 1, 3, 2, 2] {
+        r.record_access(id).unwrap();
+    }
+    for id in [1, 2, 3] {
+        r.set_evictable(id, true).unwrap();
+    }
+    assert_eq!(r.coldest(), Some(3));
+    assert_eq!(r.hottest(), Some(2));
+
+    // Pinned frames are ignored even if they are hotter.
+    r.record_access(4).unwrap();
+    r.record_access(4).unwrap();
+    assert_eq!(r.hottest(), Some(2));
+
+    // Ties: identical keys rank by id, so the larger id is evicted last.
+    let mut t = LRUKReplacer::new(8, 2);
+    admit_tied(&mut t, &[5, 6, 7], 3);
+    assert_eq!(t.coldest(), Some(5));
+    assert_eq!(t.hottest(), Some(7));
+}
+
+// --- Clock overflow ------------------------------------------------------
+
+#[test]
+fn clock_overflow_rebases_and_keeps_order() {
+    let mut r = LRUKReplacer::new(8, 2);
+    r.set_timestamp_for_test(u64::MAX - 6);
+
+    for id in [1, 1, 2, 3, 3, 4] {
+        r.record_access(id).unwrap(); // u64::MAX - 5 ..= u64::MAX
+    }
+    assert_eq!(r.access_history(4), Some(vec![u64::MAX]));
+
+    // The next tick overflows: timestamps are renumbered 1..=6, then 2 gets 7.
+    r.record_access(2).unwrap();
+    assert_eq!(r.access_history(1), Some(vec![1, 2]));
+    assert_eq!(r.access_history(2), Some(vec![3, 7]));
+    assert_eq!(r.access_history(4), Some(vec![6]));
+
+    for id in 1..=4 {
+        r.set_evictable(id, true).unwrap();
+    }
+    // ∞ first (4), then finite by oldest k-th access: 1 (1), 2 (3), 3 (4).
+    assert_eq!(evict_n(&mut r, 4), vec![4, 1, 2, 3]);
+}
+
+// --- Infinite-distance boundary ------------------------------------------
+
+#[test]
+fn is_infinite_at_k_boundary() {
+    for k in [1, 3] {
+        let mut r = LRUKReplacer::new(4, k);
+        for n in 1..=k + 1 {
+            r.record_access(1).unwrap();
+            let infinite = r.node_store[&1].is_infinite();
+            assert_eq!(infinite, n < k, "k={k}, {n} accesses");
+            assert_eq!(r.k_distance(1) == Some(u64::MAX), infinite);
+        }
+    }
+}
+
+// --- Tick ----------------------------------------------------------------
+
+#[test]
+fn tick_advances_clock_only() {
+    let mut r = LRUKReplacer::new(4, 2);
+    r.record_access(1).unwrap();
+    r.record_access(2).unwrap();
+    r.set_evictable(1, true).unwrap();
+
+    let before = r.now();
+    assert_eq!(r.tick(), before + 1);
+    assert_eq!(r.now(), before + 1);
+
+    assert_eq!(r.access_history(1), Some(vec![1]));
+    assert_eq!(r.access_history(2), Some(vec![2]));
+    assert_eq!(r.size(), 1);
+
+    // The next access lands after the tick.
+    r.record_access(1).unwrap();
+    assert_eq!(r.access_history(1), Some(vec![1, 4]));
+}
+
+// --- Single-evictable fast path ------------------------------------------
+
+#[test]
+fn single_evictable_among_pinned_is_victim() {
+    let mut r = LRUKReplacer::new(16, 2);
+    for id in 0..16 {
+        r.record_access(id).unwrap();
+    }
+    r.set_evictable(11, true).unwrap();
+
+    assert_eq!(r.size(), 1);
+    assert_eq!(r.peek_victim(), Some(11));
+    assert_eq!(r.evict(), Some(11));
+    assert_eq!(r.evict(), None);
+}
+
+#[test]
+fn single_evictable_fast_path_respects_residency_and_freeze() {
+    let mut r = LRUKReplacer::new(4, 2);
+    r.record_access(1).unwrap();
+    r.record_access(2).unwrap();
+    r.set_evictable(2, true).unwrap();
+
+    r.set_min_residency(10);
+    assert_eq!(r.peek_victim(), None);
+    r.set_min_residency(0);
+
+    r.freeze();
+    r.set_evictable(1, true).unwrap();
+    assert_eq!(r.size(), 1);
+    assert_eq!(r.peek_victim(), None);
+    r.set_evictable(1, false).unwrap();
+    r.thaw();
+    assert_eq!(r.peek_victim(), Some(2));
+}
+
+#[test]
+fn single_evictable_matches_general_comparator() {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+
+    for _ in 0..200 {
+        let mut r = LRUKReplacer::new(8, 1 + next(3));
+        for _ in 0..1 + next(24) {
+            r.record_access(next(8)).unwrap();
+        }
+        let mut ids: Vec<FrameId> = r.frame_ids().collect();
+        ids.sort_unstable();
+        let chosen = ids[next(ids.len())];
+        r.set_evictable(chosen, true).unwrap();
+
+        assert_eq!(r.size(), 1);
+        assert_eq!(r.peek_victim(), r.ranked_victims().first().copied());
+        assert_eq!(r.peek_victim(), Some(chosen));
+    }
+}
@@ -1 +1,2 @@
 mod lru_k_replacer;
+mod simulate;
@@ -0,0 +1,42 @@
+use crate::buffer::{SimResult, simulate};
+
+#[test]
+fn empty_trace_has_zero_ratio() {
+    let r = simulate(&[], 4, 2);
+    assert_eq!(
+        r,
+        SimResult {
+            hits: 0,
+            misses: 0,
+            hit_ratio: 0.0,
+            evictions: 0,
+        }
+    );
+}
+
+#[test]
+fn hand_computed_trace() {
+    // pool=3, k=2:
+    // 1 2 3 -> three cold misses filling the pool
+    // 1 2   -> hits; pages 1 and 2 now have k references
+    // 4     -> miss; page 3 is the only ∞-distance page and is evicted
+    // 1 2   -> hits
+    let r = simulate(&[1, 2, 3, 1, 2, 4, 1, 2], 3, 2);
+    assert_eq!(r.hits, 4);
+    assert_eq!(r.misses, 4);
+    assert_eq!(r.evictions, 1);
+    assert_eq!(r.hit_ratio, 0.5);
+}
+
+#[test]
+fn lru_k_resists_sequential_flooding() {
+    // A hot pair interleaved with a one-off scan: LRU-2 keeps the hot pages.
+    let mut trace = vec![100, 101, 100, 101];
+    for scan in 0..20 {
+        trace.push(scan);
+        trace.push(100 + scan % 2);
+    }
+    let r = simulate(&trace, 3, 2);
+    assert_eq!(r.misses, 2 + 20);
+    assert_eq!(r.evictions, 19);
+}